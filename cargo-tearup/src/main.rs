@@ -0,0 +1,113 @@
+//! `cargo tearup list` and `cargo tearup clean`: the resources recorded in the ledger of tearup,
+//! under `target/tearup/ledger` or `$TMPDIR/tearup/ledger`, by tests that were killed before their teardown.
+use std::{path::PathBuf, process::ExitCode};
+
+use tearup::ledger::{Entry, Ledger};
+
+const USAGE: &str = "Usage: cargo tearup <list|clean> [--target-dir <dir>]
+
+    list    Show the recorded resources, and whether the test that recorded them still runs
+    clean   Run the cleanup command of the resources of the tests no longer running";
+
+fn main() -> ExitCode {
+    // `cargo tearup ..` runs `cargo-tearup tearup ..`
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("tearup") {
+        args.next();
+    }
+
+    let mut command = None;
+    let mut target_dir = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--target-dir" => target_dir = args.next().map(PathBuf::from),
+            "list" | "clean" if command.is_none() => command = Some(arg),
+            _ => return usage(),
+        }
+    }
+
+    // The tests record in the temporary directory when their target directory isn't named `target`
+    let mut ledgers = vec![
+        Ledger::at(
+            target_dir
+                .unwrap_or_else(default_target_dir)
+                .join("tearup/ledger"),
+        ),
+        Ledger::fallback(),
+    ];
+    ledgers.dedup_by(|a, b| a.dir() == b.dir());
+
+    let mut entries = vec![];
+    for ledger in ledgers {
+        eprintln!("Reading the ledger in {}", ledger.dir().display());
+        match ledger.entries() {
+            Ok(found) => entries.extend(found),
+            Err(err) => {
+                eprintln!("Failed to read {}: {}", ledger.dir().display(), err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    match command.as_deref() {
+        Some("list") => list(entries),
+        Some("clean") => clean(entries),
+        _ => usage(),
+    }
+}
+
+fn list(entries: Vec<Entry>) -> ExitCode {
+    for entry in entries {
+        let resource = entry.resource();
+        let running = match entry.is_running() {
+            true => " (running)",
+            false => "",
+        };
+        println!(
+            "{}\t{}\tpid {}{}",
+            resource.kind,
+            resource.id,
+            entry.pid(),
+            running
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+fn clean(entries: Vec<Entry>) -> ExitCode {
+    let mut failed = false;
+    for entry in entries.into_iter().filter(|entry| !entry.is_running()) {
+        let name = format!("{} {}", entry.resource().kind, entry.resource().id);
+        match entry.clean() {
+            Ok(()) => println!("Cleaned {}", name),
+            Err(err) => {
+                eprintln!("Failed to clean {}: {}", name, err);
+                failed = true;
+            }
+        }
+    }
+
+    match failed {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
+    }
+}
+
+/// `CARGO_TARGET_DIR`, or the closest `target` from the current directory.
+fn default_target_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("CARGO_TARGET_DIR") {
+        return dir.into();
+    }
+
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    current_dir
+        .ancestors()
+        .map(|dir| dir.join("target"))
+        .find(|target| target.is_dir())
+        .unwrap_or_else(|| current_dir.join("target"))
+}
+
+fn usage() -> ExitCode {
+    eprintln!("{}", USAGE);
+    ExitCode::FAILURE
+}
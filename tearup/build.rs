@@ -0,0 +1,25 @@
+//! `native-async` needs `async fn` in traits, stable since Rust 1.75: older compilers get a clear error.
+use std::{env, process::Command};
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(tearup_before_1_75)");
+    if env::var_os("CARGO_FEATURE_NATIVE_ASYNC").is_none() {
+        return;
+    }
+
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let minor = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| minor_version(&String::from_utf8_lossy(&output.stdout)));
+    if matches!(minor, Some(minor) if minor < 75) {
+        println!("cargo:rustc-cfg=tearup_before_1_75");
+    }
+}
+
+/// `75` for `rustc 1.75.0 (82e1608df 2023-12-21)`.
+fn minor_version(version: &str) -> Option<u32> {
+    let version = version.split_whitespace().nth(1)?;
+    version.split('.').nth(1)?.parse().ok()
+}
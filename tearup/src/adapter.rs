@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use crate::{
+    runtime::{spawn_blocking, BlockingRuntime},
+    AsyncContext, AsyncSharedContext, Context, LeakCheck, Serial, SharedContext, TearupObserver,
+    TestOutcome,
+};
+
+/// Use an `AsyncContext` as a sync `Context`, e.g. in a sync test or a `ContextCombinator`.
+/// The setup and the teardown are driven on a runtime owned by the adapter, alive until the teardown.
+///
+/// ```ignore
+/// type SyncServerContext = Blocking<ServerContext>;
+/// #[tearup_test(SyncServerContext)]
+/// fn it_should_do_this(address: Address) {}
+/// ```
+pub struct Blocking<C> {
+    context: C,
+    shared_context: AsyncSharedContext,
+    runtime: BlockingRuntime,
+}
+
+impl<C> Context for Blocking<C>
+where
+    C: AsyncContext,
+{
+    const LEAK_CHECK: Option<LeakCheck> = C::LEAK_CHECK;
+
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        let runtime = BlockingRuntime::new();
+        let async_shared_context = AsyncSharedContext::default();
+
+        let context = runtime.block_on(async {
+            async_shared_context.merge(shared_context.clone()).await;
+            let context = C::launch_setup(async_shared_context.clone()).await;
+            shared_context.merge(&async_shared_context.snapshot().await);
+            context
+        });
+
+        Self {
+            context,
+            shared_context: async_shared_context,
+            runtime,
+        }
+    }
+
+    fn teardown(self, shared_context: &mut SharedContext) {
+        let Self {
+            context,
+            shared_context: async_shared_context,
+            runtime,
+        } = self;
+
+        runtime.block_on(async {
+            async_shared_context.merge(shared_context.clone()).await;
+            context.launch_teardown(async_shared_context.clone()).await;
+            shared_context.merge(&async_shared_context.snapshot().await);
+        });
+    }
+
+    fn serial() -> Vec<Serial> {
+        C::serial()
+    }
+
+    fn observers() -> Vec<Arc<dyn TearupObserver>> {
+        C::observers()
+    }
+
+    fn fallback_teardown(outcome: &TestOutcome) -> bool {
+        C::fallback_teardown(outcome)
+    }
+}
+
+/// Use a sync `Context` as an `AsyncContext`, e.g. in an async test or an `AsyncContextCombinator`.
+/// The setup and the teardown run on the blocking pool of the runtime.
+///
+/// ```ignore
+/// type Both = AsyncContextCombinator<Async<TempDirContext>, ServerContext>;
+/// #[tearup_test(Both)]
+/// async fn it_should_do_this(dir: TempDir, address: Address) {}
+/// ```
+pub struct Async<C>(C);
+
+#[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+impl<C> AsyncContext for Async<C>
+where
+    C: Context + Send + Sync + 'static,
+{
+    const LEAK_CHECK: Option<LeakCheck> = C::LEAK_CHECK;
+
+    async fn setup(shared_context: AsyncSharedContext) -> Self {
+        let mut sync_shared_context = shared_context.snapshot().await;
+
+        let (context, sync_shared_context) = spawn_blocking(move || {
+            let context = C::launch_setup(&mut sync_shared_context);
+            (context, sync_shared_context)
+        })
+        .await;
+
+        shared_context.merge(sync_shared_context).await;
+        Self(context)
+    }
+
+    async fn teardown(self, shared_context: AsyncSharedContext) {
+        let mut sync_shared_context = shared_context.snapshot().await;
+
+        let sync_shared_context = spawn_blocking(move || {
+            self.0.launch_teardown(&mut sync_shared_context);
+            sync_shared_context
+        })
+        .await;
+
+        shared_context.merge(sync_shared_context).await;
+    }
+
+    fn serial() -> Vec<Serial> {
+        C::serial()
+    }
+
+    fn observers() -> Vec<Arc<dyn TearupObserver>> {
+        C::observers()
+    }
+
+    fn fallback_teardown(outcome: &TestOutcome) -> bool {
+        C::fallback_teardown(outcome)
+    }
+}
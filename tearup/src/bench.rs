@@ -0,0 +1,181 @@
+use criterion::{measurement::WallTime, BenchmarkGroup, Bencher, Criterion};
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use crate::{Context, SharedContext};
+#[cfg(feature = "tokio")]
+pub use asyncc::*;
+
+/// Benchmark `name` against a context.
+/// The setup and the teardown run once, outside of the measured section.
+///
+/// ```ignore
+/// with_context::<DbContext, _>(c, "insert", |fixtures, b| {
+///     let mut db = fixtures.get::<DbClient>().unwrap();
+///     b.iter(|| db.insert("something"))
+/// });
+/// ```
+pub fn with_context<C, F>(c: &mut Criterion, name: &str, mut f: F)
+where
+    C: Context,
+    F: FnMut(&mut SharedContext, &mut Bencher),
+{
+    run_with_context::<C, _>(|shared_context| {
+        c.bench_function(name, |b| f(shared_context, b));
+    });
+}
+
+/// Same as `with_context` but gives the whole `BenchmarkGroup` so several benchmarks share the same setup.
+pub fn with_context_group<C, F>(c: &mut Criterion, name: &str, f: F)
+where
+    C: Context,
+    F: FnOnce(&mut SharedContext, &mut BenchmarkGroup<WallTime>),
+{
+    run_with_context::<C, _>(|shared_context| {
+        let mut group = c.benchmark_group(name);
+        f(shared_context, &mut group);
+        group.finish();
+    });
+}
+
+fn run_with_context<C: Context, F: FnOnce(&mut SharedContext)>(bench: F) {
+    let mut shared_context = SharedContext::default();
+    let context = C::launch_setup(&mut shared_context);
+
+    let bench_execution = catch_unwind(AssertUnwindSafe(|| bench(&mut shared_context)));
+
+    context.launch_teardown(&mut shared_context);
+
+    if let Err(err) = bench_execution {
+        resume_unwind(err)
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod asyncc {
+    use criterion::{measurement::WallTime, BenchmarkGroup, Bencher, Criterion};
+    use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+    use tokio::runtime::{Builder, Runtime};
+
+    use crate::{AsyncContext, AsyncSharedContext};
+
+    /// What a benchmark of an `AsyncContext` can access: the shared context and the runtime owned by the helper.
+    pub struct AsyncFixtures {
+        shared_context: AsyncSharedContext,
+        runtime: Runtime,
+    }
+
+    impl AsyncFixtures {
+        /// Get a value registered by the context, outside of the measured section.
+        pub fn get<T: 'static + Send + Clone>(&mut self) -> Option<T> {
+            self.runtime.block_on(self.shared_context.get())
+        }
+
+        /// The runtime driving the context, use it with `b.to_async(fixtures.runtime())`.
+        pub fn runtime(&self) -> &Runtime {
+            &self.runtime
+        }
+    }
+
+    /// Same as `with_context` for an `AsyncContext`, driven on a runtime owned by the helper.
+    pub fn with_async_context<C, F>(c: &mut Criterion, name: &str, mut f: F)
+    where
+        C: AsyncContext,
+        F: FnMut(&mut AsyncFixtures, &mut Bencher),
+    {
+        run_with_async_context::<C, _>(|fixtures| {
+            c.bench_function(name, |b| f(fixtures, b));
+        });
+    }
+
+    /// Same as `with_context_group` for an `AsyncContext`, driven on a runtime owned by the helper.
+    pub fn with_async_context_group<C, F>(c: &mut Criterion, name: &str, f: F)
+    where
+        C: AsyncContext,
+        F: FnOnce(&mut AsyncFixtures, &mut BenchmarkGroup<WallTime>),
+    {
+        run_with_async_context::<C, _>(|fixtures| {
+            let mut group = c.benchmark_group(name);
+            f(fixtures, &mut group);
+            group.finish();
+        });
+    }
+
+    fn run_with_async_context<C, F>(bench: F)
+    where
+        C: AsyncContext,
+        F: FnOnce(&mut AsyncFixtures),
+    {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to build the benchmark runtime");
+        let shared_context = AsyncSharedContext::default();
+        let context = runtime.block_on(C::launch_setup(shared_context.clone()));
+
+        let mut fixtures = AsyncFixtures {
+            shared_context,
+            runtime,
+        };
+        let bench_execution = catch_unwind(AssertUnwindSafe(|| bench(&mut fixtures)));
+
+        let AsyncFixtures {
+            shared_context,
+            runtime,
+        } = fixtures;
+        runtime.block_on(context.launch_teardown(shared_context));
+
+        if let Err(err) = bench_execution {
+            resume_unwind(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use criterion::Criterion;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::with_context_group;
+    use crate::{Context, SharedContext};
+
+    static SETUPS: AtomicUsize = AtomicUsize::new(0);
+    static TEARDOWNS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Clone)]
+    struct Proof(usize);
+
+    struct CountingContext;
+    impl Context for CountingContext {
+        fn setup(shared_context: &mut SharedContext) -> Self {
+            SETUPS.fetch_add(1, Ordering::SeqCst);
+            shared_context.register(Proof(42));
+            Self {}
+        }
+
+        fn teardown(self, _shared_context: &mut SharedContext) {
+            TEARDOWNS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn it_setups_and_teardowns_once_per_group() {
+        let mut c = Criterion::default()
+            .sample_size(10)
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(10))
+            .nresamples(1000)
+            .without_plots();
+
+        with_context_group::<CountingContext, _>(&mut c, "tearup", |fixtures, group| {
+            let proof = fixtures.get::<Proof>().unwrap();
+            group.bench_function("first", |b| b.iter(|| proof.0 + 1));
+            group.bench_function("second", |b| b.iter(|| proof.0 + 2));
+        });
+
+        assert_eq!(SETUPS.load(Ordering::SeqCst), 1);
+        assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,396 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+use stopwatch::Stopwatch;
+
+#[cfg(feature = "async")]
+use crate::AsyncSharedContext;
+use crate::SharedContext;
+
+static SLOTS: Mutex<Option<HashMap<&'static str, Arc<Slots>>>> = Mutex::new(None);
+
+/// Time spent by a test waiting for a context limited by `MAX_CONCURRENT`,
+/// one per limited context of the test, registered in the shared context before its setup.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConcurrencyWaits(pub Vec<ConcurrencyWait>);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConcurrencyWait {
+    pub context: &'static str,
+    pub waited: Duration,
+}
+
+impl ConcurrencyWaits {
+    pub fn total(&self) -> Duration {
+        self.0.iter().map(|wait| wait.waited).sum()
+    }
+
+    pub(crate) fn record(shared_context: &mut SharedContext, wait: ConcurrencyWait) {
+        let mut waits = shared_context.get::<Self>().unwrap_or_default();
+        waits.0.push(wait);
+        shared_context.register(waits);
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) async fn record_async(
+        shared_context: &mut AsyncSharedContext,
+        wait: ConcurrencyWait,
+    ) {
+        let mut waits = shared_context.get::<Self>().await.unwrap_or_default();
+        waits.0.push(wait);
+        shared_context.register(waits).await;
+    }
+}
+
+/// The slots taken by the setups of a test, held by the macros from before the setup to the end of the test.
+/// The ones not given back by a teardown, e.g. when getting a fixture panicked, are released on drop.
+pub struct SlotScope {
+    held: HeldSlots,
+}
+
+impl Default for SlotScope {
+    fn default() -> Self {
+        Self {
+            held: HeldSlots(Some(Arc::default())),
+        }
+    }
+}
+
+impl SlotScope {
+    /// To register in the shared context before the setup.
+    pub fn held(&self) -> HeldSlots {
+        self.held.clone()
+    }
+}
+
+impl Drop for SlotScope {
+    fn drop(&mut self) {
+        if let Some(held) = &self.held.0 {
+            let left = std::mem::take(&mut *held.lock().unwrap_or_else(|p| p.into_inner()));
+            for (_, slots) in left {
+                slots.release();
+            }
+        }
+    }
+}
+
+type Held = Vec<(&'static str, Arc<Slots>)>;
+
+/// The slots of a `SlotScope`, by context.
+#[derive(Clone)]
+pub struct HeldSlots(Option<Arc<Mutex<Held>>>);
+
+impl HeldSlots {
+    /// For the contexts outliving the test, e.g. pooled: their slots are kept until their teardown.
+    pub(crate) fn detached() -> Self {
+        Self(None)
+    }
+
+    fn count<C>(&self) -> usize {
+        let context = std::any::type_name::<C>();
+        match &self.0 {
+            Some(held) => {
+                let held = held.lock().unwrap();
+                held.iter().filter(|(held, _)| *held == context).count()
+            }
+            None => 0,
+        }
+    }
+
+    fn push<C>(&self, slots: Arc<Slots>) {
+        if let Some(held) = &self.0 {
+            let context = std::any::type_name::<C>();
+            held.lock().unwrap().push((context, slots));
+        }
+    }
+
+    fn take<C>(&self, max: usize) -> Option<Arc<Slots>> {
+        let context = std::any::type_name::<C>();
+        match &self.0 {
+            Some(held) => {
+                let mut held = held.lock().unwrap();
+                let index = held.iter().rposition(|(held, _)| *held == context)?;
+                Some(held.remove(index).1)
+            }
+            None => Slots::of::<C>(max),
+        }
+    }
+}
+
+/// A live instance of a context counted against its `MAX_CONCURRENT`.
+/// Released on drop unless kept by `keep`, then `held` takes it back for the teardown.
+pub(crate) struct Slot {
+    slots: Option<Arc<Slots>>,
+}
+
+impl Slot {
+    /// Wait for an instance of the context to go away if `max` are already alive.
+    pub(crate) fn acquire<C>(
+        max: usize,
+        scope: Option<&HeldSlots>,
+    ) -> (Self, Option<ConcurrencyWait>) {
+        let slots = match Slots::of::<C>(max) {
+            Some(slots) => slots,
+            None => return (Self { slots: None }, None),
+        };
+
+        let stopwatch = Stopwatch::start_new();
+        let blocked = !slots.try_acquire();
+        if blocked {
+            check_reentrance::<C>(max, scope);
+            slots.acquire();
+        }
+        let slot = Self { slots: Some(slots) };
+        Self::acquired::<C>(slot, max, blocked, stopwatch.elapsed())
+    }
+
+    /// Same as `acquire`, waiting on the blocking pool of the runtime.
+    /// The slot taken once the future is dropped is released.
+    #[cfg(feature = "async")]
+    pub(crate) async fn acquire_async<C>(
+        max: usize,
+        scope: Option<&HeldSlots>,
+    ) -> (Self, Option<ConcurrencyWait>) {
+        let slots = match Slots::of::<C>(max) {
+            Some(slots) => slots,
+            None => return (Self { slots: None }, None),
+        };
+
+        let stopwatch = Stopwatch::start_new();
+        let blocked = !slots.try_acquire();
+        let slot = if blocked {
+            check_reentrance::<C>(max, scope);
+            crate::runtime::spawn_blocking(move || {
+                slots.acquire();
+                // Dropped with the output of the task if nobody awaits it anymore
+                Self { slots: Some(slots) }
+            })
+            .await
+        } else {
+            Self { slots: Some(slots) }
+        };
+        Self::acquired::<C>(slot, max, blocked, stopwatch.elapsed())
+    }
+
+    fn acquired<C>(
+        slot: Self,
+        max: usize,
+        blocked: bool,
+        waited: Duration,
+    ) -> (Self, Option<ConcurrencyWait>) {
+        let wait = ConcurrencyWait {
+            context: std::any::type_name::<C>(),
+            waited,
+        };
+        if blocked {
+            eprintln!(
+                "{} waited {:?} for one of its {} slots",
+                wait.context, wait.waited, max
+            );
+        }
+
+        (slot, Some(wait))
+    }
+
+    /// The slot stays taken until the teardown, or the end of the scope.
+    pub(crate) fn keep<C>(mut self, scope: Option<&HeldSlots>) {
+        if let (Some(slots), Some(scope)) = (self.slots.take(), scope) {
+            scope.push::<C>(slots);
+        }
+    }
+
+    /// The slot kept by the setup of an instance of the context.
+    pub(crate) fn held<C>(max: usize, scope: Option<&HeldSlots>) -> Self {
+        let slots = match scope {
+            Some(scope) => scope.take::<C>(max),
+            None => Slots::of::<C>(max),
+        };
+        Self { slots }
+    }
+}
+
+/// Waiting for a slot the test holds itself never ends, e.g. `ContextCombinator<A, A>`.
+fn check_reentrance<C>(max: usize, scope: Option<&HeldSlots>) {
+    if scope.map_or(0, |scope| scope.count::<C>()) >= max.max(1) {
+        panic!(
+            "{} is set up more than its MAX_CONCURRENT of {} times by the same test",
+            std::any::type_name::<C>(),
+            max
+        );
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        if let Some(slots) = self.slots.take() {
+            slots.release();
+        }
+    }
+}
+
+pub struct Slots {
+    available: Mutex<usize>,
+    released: Condvar,
+}
+
+impl Slots {
+    /// `None` when the context is not limited.
+    fn of<C>(max: usize) -> Option<Arc<Self>> {
+        if max == usize::MAX {
+            return None;
+        }
+
+        let mut slots = SLOTS.lock().unwrap();
+        let slots = slots
+            .get_or_insert_with(HashMap::new)
+            .entry(std::any::type_name::<C>())
+            .or_insert_with(|| {
+                Arc::new(Self {
+                    available: Mutex::new(max.max(1)),
+                    released: Condvar::new(),
+                })
+            });
+        Some(slots.clone())
+    }
+
+    fn try_acquire(&self) -> bool {
+        let mut available = self.available.lock().unwrap();
+        if *available == 0 {
+            return false;
+        }
+        *available -= 1;
+        true
+    }
+
+    fn acquire(&self) {
+        let mut available = self.available.lock().unwrap();
+        while *available == 0 {
+            available = self.released.wait(available).unwrap();
+        }
+        *available -= 1;
+    }
+
+    fn release(&self) {
+        *self.available.lock().unwrap() += 1;
+        self.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        panic::catch_unwind,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread::{sleep, spawn},
+        time::Duration,
+    };
+
+    use super::{Slot, SlotScope};
+
+    struct Limited;
+
+    #[test]
+    fn it_limits_the_live_instances() {
+        let live = Arc::new(AtomicUsize::new(0));
+        let max_live = Arc::new(AtomicUsize::new(0));
+
+        let instances = (0..6)
+            .map(|_| {
+                let (live, max_live) = (live.clone(), max_live.clone());
+                spawn(move || {
+                    let (slot, wait) = Slot::acquire::<Limited>(2, None);
+                    slot.keep::<Limited>(None);
+                    max_live.fetch_max(live.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    sleep(Duration::from_millis(20));
+                    live.fetch_sub(1, Ordering::SeqCst);
+                    drop(Slot::held::<Limited>(2, None));
+                    wait.unwrap().waited
+                })
+            })
+            .collect::<Vec<_>>();
+        let waits = instances
+            .into_iter()
+            .map(|instance| instance.join().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(max_live.load(Ordering::SeqCst), 2);
+        assert!(waits
+            .iter()
+            .any(|waited| *waited >= Duration::from_millis(15)));
+    }
+
+    #[test]
+    fn it_releases_the_slot_when_not_kept() {
+        struct SingleSlot;
+
+        let (slot, _) = Slot::acquire::<SingleSlot>(1, None);
+        drop(slot);
+
+        let (slot, wait) = Slot::acquire::<SingleSlot>(1, None);
+        slot.keep::<SingleSlot>(None);
+        assert!(wait.unwrap().waited < Duration::from_millis(10));
+    }
+
+    #[test]
+    fn it_releases_the_slots_left_in_the_scope() {
+        struct ScopedSlot;
+
+        let setup = catch_unwind(|| {
+            let scope = SlotScope::default();
+            let (slot, _) = Slot::acquire::<ScopedSlot>(1, Some(&scope.held()));
+            slot.keep::<ScopedSlot>(Some(&scope.held()));
+            panic!("fixture missing");
+        });
+        assert!(setup.is_err());
+
+        let scope = SlotScope::default();
+        let (slot, wait) = Slot::acquire::<ScopedSlot>(1, Some(&scope.held()));
+        slot.keep::<ScopedSlot>(Some(&scope.held()));
+        assert!(wait.unwrap().waited < Duration::from_millis(10));
+        drop(Slot::held::<ScopedSlot>(1, Some(&scope.held())));
+        assert!(scope.held().count::<ScopedSlot>() == 0);
+    }
+
+    #[test]
+    fn it_panics_instead_of_waiting_for_its_own_slot() {
+        struct Reentrant;
+
+        let scope = SlotScope::default();
+        let (slot, _) = Slot::acquire::<Reentrant>(1, Some(&scope.held()));
+        slot.keep::<Reentrant>(Some(&scope.held()));
+
+        let again = catch_unwind(|| Slot::acquire::<Reentrant>(1, Some(&scope.held())));
+        assert!(again.is_err());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn it_releases_the_slot_of_a_cancelled_acquisition() {
+        struct Cancelled;
+
+        let (slot, _) = Slot::acquire::<Cancelled>(1, None);
+        let acquisition = Slot::acquire_async::<Cancelled>(1, None);
+        let cancelled = tokio::time::timeout(Duration::from_millis(10), acquisition).await;
+        assert!(cancelled.is_err());
+        drop(slot);
+
+        // Taken then released by the cancelled acquisition
+        let (_slot, wait) = tokio::task::spawn_blocking(|| Slot::acquire::<Cancelled>(1, None))
+            .await
+            .unwrap();
+        assert!(wait.is_some());
+    }
+
+    #[test]
+    fn it_does_not_limit_by_default() {
+        let (slot, wait) = Slot::acquire::<Limited>(usize::MAX, None);
+
+        assert!(slot.slots.is_none());
+        assert!(wait.is_none());
+    }
+}
@@ -1,11 +1,23 @@
-use std::any::Any;
+use std::{any::Any, sync::Arc};
 
-use crate::SharedContext;
+use crate::{
+    concurrency::{ConcurrencyWaits, HeldSlots, Slot},
+    LeakCheck, Observation, Serial, SharedContext, TearupEventKind, TearupObserver, TestName,
+    TestOutcome,
+};
 #[cfg(feature = "async")]
 pub use asyncc::*;
 
 /// Trait to implement to use the `#[tearup_test]` or `#[tearup]`
 pub trait Context: Sized {
+    /// Live instances of the context at once in the test binary,
+    /// the next setups wait for a teardown, see `ConcurrencyWaits`.
+    const MAX_CONCURRENT: usize = usize::MAX;
+
+    /// What to do with the resources still open after the teardown, see `LeakCheck`.
+    /// `None` follows `TEARUP_LEAK_CHECK`.
+    const LEAK_CHECK: Option<LeakCheck> = None;
+
     /// Will be executed before the test execution
     /// You should prepare all your test requirement here.
     /// Use the `ready` to notify that the test can start
@@ -17,11 +29,43 @@ pub trait Context: Sized {
     /// You should do your clean up here.
     fn teardown(self, _shared_context: &mut SharedContext);
 
+    /// The groups of tests this context must not run concurrently with, see `Serial`.
+    fn serial() -> Vec<Serial> {
+        vec![]
+    }
+
+    /// Run by the parent process of an `isolate = "process"` test when the child died before its teardown,
+    /// and on SIGINT or SIGTERM for the live contexts. Returns whether the context was cleaned up,
+    /// the default does nothing so its contexts are reported as left behind.
+    fn fallback_teardown(_outcome: &TestOutcome) -> bool {
+        false
+    }
+
+    /// Receive the events of this context, in addition to the observers added with `add_observer`.
+    fn observers() -> Vec<Arc<dyn TearupObserver>> {
+        vec![]
+    }
+
     fn launch_setup(shared_context: &mut SharedContext) -> Self {
-        Self::setup(shared_context)
+        let scope = shared_context.get::<HeldSlots>();
+        let (slot, wait) = Slot::acquire::<Self>(Self::MAX_CONCURRENT, scope.as_ref());
+        if let Some(wait) = wait {
+            ConcurrencyWaits::record(shared_context, wait);
+        }
+
+        let observation = Observation::start(
+            std::any::type_name::<Self>(),
+            shared_context.get::<TestName>().map(|test| test.0),
+            Self::observers(),
+            TearupEventKind::SetupStarted,
+        );
+        let context = observation.in_scope(|| Self::setup(shared_context));
+        observation.finish(|duration| TearupEventKind::SetupFinished { duration });
+        slot.keep::<Self>(scope.as_ref());
+        context
     }
 
-    fn launch_test<TestFn>(&mut self, test: TestFn) -> Result<(), Box<dyn Any + Send>>
+    fn launch_test<TestFn>(test: TestFn) -> Result<(), Box<dyn Any + Send>>
     where
         TestFn: FnOnce(),
         Self: Sized,
@@ -30,22 +74,44 @@ pub trait Context: Sized {
     }
 
     fn launch_teardown(self, shared_context: &mut SharedContext) {
-        self.teardown(shared_context);
+        let scope = shared_context.get::<HeldSlots>();
+        let _slot = Slot::held::<Self>(Self::MAX_CONCURRENT, scope.as_ref());
+        let observation = Observation::start(
+            std::any::type_name::<Self>(),
+            shared_context.get::<TestName>().map(|test| test.0),
+            Self::observers(),
+            TearupEventKind::TeardownStarted,
+        );
+        observation.in_scope(|| self.teardown(shared_context));
+        observation.finish(|duration| TearupEventKind::TeardownFinished { duration });
     }
 }
 
 #[cfg(feature = "async")]
 mod asyncc {
-    use async_trait::async_trait;
-    use futures::future::BoxFuture;
     pub use futures::future::FutureExt;
-    use std::{any::Any, panic::AssertUnwindSafe};
+    use std::{any::Any, future::Future, panic::AssertUnwindSafe, sync::Arc};
 
-    use crate::AsyncSharedContext;
+    use crate::{
+        concurrency::{ConcurrencyWaits, HeldSlots, Slot},
+        AsyncSharedContext, LeakCheck, Observation, Serial, TearupEventKind, TearupObserver,
+        TestName, TestOutcome,
+    };
 
     /// Trait to implement to use the `#[tearup_test]` or `#[tearup]`
-    #[async_trait]
-    pub trait AsyncContext<'a>: Sync + Send + Sized {
+    ///
+    /// Implement it with `#[async_trait]`, or with plain `async fn` when the `native-async` feature is on (Rust 1.75+).
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    #[cfg_attr(feature = "native-async", allow(async_fn_in_trait))]
+    pub trait AsyncContext: Sync + Send + Sized {
+        /// Live instances of the context at once in the test binary,
+        /// the next setups wait for a teardown, see `ConcurrencyWaits`.
+        const MAX_CONCURRENT: usize = usize::MAX;
+
+        /// What to do with the resources still open after the teardown, see `LeakCheck`.
+        /// `None` follows `TEARUP_LEAK_CHECK`.
+        const LEAK_CHECK: Option<LeakCheck> = None;
+
         /// Will be executed before the test execution
         /// You should prepare all your test requirement here.
         /// Use the `ready` to notify that the test can start
@@ -55,18 +121,52 @@ mod asyncc {
 
         /// Will be executed before the test execution even if the test has panicked
         /// You should do your clean up here.
-        async fn teardown(mut self, shared_context: AsyncSharedContext);
+        async fn teardown(self, shared_context: AsyncSharedContext);
 
-        async fn launch_setup(shared_context: AsyncSharedContext) -> Self
+        /// The groups of tests this context must not run concurrently with, see `Serial`.
+        fn serial() -> Vec<Serial> {
+            vec![]
+        }
+
+        /// Run by the parent process of an `isolate = "process"` test when the child died before its teardown,
+        /// and on SIGINT or SIGTERM for the live contexts. Returns whether the context was cleaned up,
+        /// the default does nothing so its contexts are reported as left behind.
+        fn fallback_teardown(_outcome: &TestOutcome) -> bool {
+            false
+        }
+
+        /// Receive the events of this context, in addition to the observers added with `add_observer`.
+        fn observers() -> Vec<Arc<dyn TearupObserver>> {
+            vec![]
+        }
+
+        async fn launch_setup(mut shared_context: AsyncSharedContext) -> Self
         where
             Self: Sized,
         {
-            Self::setup(shared_context).await
+            let scope = shared_context.get::<HeldSlots>().await;
+            let (slot, wait) =
+                Slot::acquire_async::<Self>(Self::MAX_CONCURRENT, scope.as_ref()).await;
+            if let Some(wait) = wait {
+                ConcurrencyWaits::record_async(&mut shared_context, wait).await;
+            }
+
+            let observation = Observation::start(
+                std::any::type_name::<Self>(),
+                shared_context.get::<TestName>().await.map(|test| test.0),
+                Self::observers(),
+                TearupEventKind::SetupStarted,
+            );
+            let context = observation.instrument(Self::setup(shared_context)).await;
+            observation.finish(|duration| TearupEventKind::SetupFinished { duration });
+            slot.keep::<Self>(scope.as_ref());
+            context
         }
 
-        async fn launch_test<TestFn>(&mut self, test: TestFn) -> Result<(), Box<dyn Any + Send>>
+        async fn launch_test<TestFn, TestFuture>(test: TestFn) -> Result<(), Box<dyn Any + Send>>
         where
-            TestFn: FnOnce() -> BoxFuture<'a, ()> + Send,
+            TestFn: FnOnce() -> TestFuture + Send,
+            TestFuture: Future<Output = ()> + Send,
             Self: Sized,
         {
             AssertUnwindSafe(async move { test().await })
@@ -74,8 +174,17 @@ mod asyncc {
                 .await
         }
 
-        async fn launch_teardown(mut self, shared_context: AsyncSharedContext) {
-            self.teardown(shared_context).await;
+        async fn launch_teardown(self, mut shared_context: AsyncSharedContext) {
+            let scope = shared_context.get::<HeldSlots>().await;
+            let _slot = Slot::held::<Self>(Self::MAX_CONCURRENT, scope.as_ref());
+            let observation = Observation::start(
+                std::any::type_name::<Self>(),
+                shared_context.get::<TestName>().await.map(|test| test.0),
+                Self::observers(),
+                TearupEventKind::TeardownStarted,
+            );
+            observation.instrument(self.teardown(shared_context)).await;
+            observation.finish(|duration| TearupEventKind::TeardownFinished { duration });
         }
     }
 }
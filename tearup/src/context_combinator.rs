@@ -1,6 +1,8 @@
 pub use tearup_macro::{tearup, tearup_test};
 
-use crate::{Context, SharedContext};
+use std::sync::Arc;
+
+use crate::{Context, LeakCheck, Serial, SharedContext, TearupObserver, TestOutcome};
 #[cfg(feature = "async")]
 pub use asyncc::*;
 
@@ -10,6 +12,9 @@ pub struct ContextCombinator<Context1: Context, Context2: Context> {
 }
 
 impl<Context1: Context, Context2: Context> Context for ContextCombinator<Context1, Context2> {
+    const LEAK_CHECK: Option<LeakCheck> =
+        LeakCheck::strictest(Context1::LEAK_CHECK, Context2::LEAK_CHECK);
+
     /// Will be executed before the test execution
     /// You should prepare all your test requirement here.
     /// Use the `ready` to notify that the test can start
@@ -26,30 +31,51 @@ impl<Context1: Context, Context2: Context> Context for ContextCombinator<Context
         self.context1.launch_teardown(shared_context);
         self.context2.launch_teardown(shared_context);
     }
+
+    fn serial() -> Vec<Serial> {
+        let mut groups = Context1::serial();
+        groups.extend(Context2::serial());
+        groups
+    }
+
+    fn observers() -> Vec<Arc<dyn TearupObserver>> {
+        let mut observers = Context1::observers();
+        observers.extend(Context2::observers());
+        observers
+    }
+
+    fn fallback_teardown(outcome: &TestOutcome) -> bool {
+        // Both run even if the first one has nothing to clean up
+        Context1::fallback_teardown(outcome) & Context2::fallback_teardown(outcome)
+    }
 }
 
 #[cfg(feature = "async")]
 mod asyncc {
-    use async_trait::async_trait;
     pub use tearup_macro::{tearup, tearup_test};
 
-    use crate::{AsyncContext, AsyncSharedContext};
+    use std::sync::Arc;
+
+    use crate::{AsyncContext, AsyncSharedContext, LeakCheck, Serial, TearupObserver, TestOutcome};
 
     pub struct AsyncContextCombinator<Context1, Context2>
     where
-        for<'a> Context1: AsyncContext<'a> + Send,
-        for<'a> Context2: AsyncContext<'a> + Send,
+        Context1: AsyncContext,
+        Context2: AsyncContext,
     {
         context1: Context1,
         context2: Context2,
     }
 
-    #[async_trait]
-    impl<Context1, Context2> AsyncContext<'_> for AsyncContextCombinator<Context1, Context2>
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl<Context1, Context2> AsyncContext for AsyncContextCombinator<Context1, Context2>
     where
-        for<'a> Context1: AsyncContext<'a> + Send,
-        for<'a> Context2: AsyncContext<'a> + Send,
+        Context1: AsyncContext,
+        Context2: AsyncContext,
     {
+        const LEAK_CHECK: Option<LeakCheck> =
+            LeakCheck::strictest(Context1::LEAK_CHECK, Context2::LEAK_CHECK);
+
         async fn setup(shared_context: AsyncSharedContext) -> Self {
             let context1 = Context1::launch_setup(shared_context.clone()).await;
             let context2 = Context2::launch_setup(shared_context).await;
@@ -58,9 +84,25 @@ mod asyncc {
 
         /// Will be executed before the test execution even if the test has panicked
         /// You should do your clean up here.
-        async fn teardown(mut self, shared_context: AsyncSharedContext) {
-            self.context1.teardown(shared_context.clone()).await;
-            self.context2.teardown(shared_context).await;
+        async fn teardown(self, shared_context: AsyncSharedContext) {
+            self.context1.launch_teardown(shared_context.clone()).await;
+            self.context2.launch_teardown(shared_context).await;
+        }
+
+        fn serial() -> Vec<Serial> {
+            let mut groups = Context1::serial();
+            groups.extend(Context2::serial());
+            groups
+        }
+
+        fn observers() -> Vec<Arc<dyn TearupObserver>> {
+            let mut observers = Context1::observers();
+            observers.extend(Context2::observers());
+            observers
+        }
+
+        fn fallback_teardown(outcome: &TestOutcome) -> bool {
+            Context1::fallback_teardown(outcome) & Context2::fallback_teardown(outcome)
         }
     }
 }
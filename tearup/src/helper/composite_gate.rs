@@ -0,0 +1,601 @@
+#[cfg(feature = "async")]
+pub use asyncc::*;
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+use stopwatch::Stopwatch;
+
+use super::{Bell, ReadyFn, TimeGate};
+use crate::TimeoutError;
+
+/// Blocks until its notifiers have been called `count` times,
+/// e.g. one call per worker reporting ready.
+pub struct CountdownGate {
+    remaining: Arc<(Mutex<usize>, Condvar)>,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct CountdownTimeoutError {
+    pub timeout: TimeoutError,
+    pub remaining: usize,
+}
+
+impl CountdownGate {
+    pub fn new(count: usize) -> Self {
+        CountdownGate {
+            remaining: Arc::new((Mutex::new(count), Condvar::new())),
+        }
+    }
+
+    pub fn notifier(&self) -> ReadyFn {
+        let remaining = self.remaining.clone();
+
+        Box::new(move || {
+            let (count, condvar) = &*remaining;
+            let mut count = count.lock().unwrap();
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                condvar.notify_all();
+            }
+        })
+    }
+
+    pub fn wait_signal(self) {
+        let (count, condvar) = &*self.remaining;
+        let _count = condvar
+            .wait_while(count.lock().unwrap(), |count| *count > 0)
+            .unwrap();
+    }
+
+    pub fn wait_signal_or_timeout(self, timeout: Duration) -> Result<(), CountdownTimeoutError> {
+        let (count, condvar) = &*self.remaining;
+        let (count, _) = condvar
+            .wait_timeout_while(count.lock().unwrap(), timeout, |count| *count > 0)
+            .unwrap();
+
+        match *count {
+            0 => Ok(()),
+            remaining => Err(CountdownTimeoutError {
+                timeout: TimeoutError {
+                    duration: timeout,
+                    ready_checks_interval: None,
+                },
+                remaining,
+            }),
+        }
+    }
+}
+
+/// Why a composite gate has not been opened.
+#[derive(PartialEq, Debug)]
+pub enum CompositeGateError<E> {
+    /// The deadline passed before the `pending` members sent their signal.
+    Timeout {
+        timeout: TimeoutError,
+        pending: Vec<String>,
+    },
+    Failed {
+        member: String,
+        error: E,
+    },
+}
+
+/// Opens once every named gate is ready, fails as soon as one of them fails.
+pub struct AllGates<T, E> {
+    members: Vec<(String, TimeGate<T, E>)>,
+}
+
+impl<T, E> AllGates<T, E> {
+    pub fn new<N: Into<String>>(members: impl IntoIterator<Item = (N, TimeGate<T, E>)>) -> Self {
+        AllGates {
+            members: named(members),
+        }
+    }
+
+    /// Waits every member against the same deadline, the payloads come in the members order.
+    pub fn wait_signal_or_timeout(
+        self,
+        timeout: Duration,
+    ) -> Result<Vec<T>, CompositeGateError<E>> {
+        wait_members(self.members, timeout, Progress::all)
+    }
+}
+
+/// Opens as soon as one of the named gates is ready, fails once all of them failed.
+pub struct AnyGates<T, E> {
+    members: Vec<(String, TimeGate<T, E>)>,
+}
+
+impl<T, E> AnyGates<T, E> {
+    pub fn new<N: Into<String>>(members: impl IntoIterator<Item = (N, TimeGate<T, E>)>) -> Self {
+        AnyGates {
+            members: named(members),
+        }
+    }
+
+    /// Gives the name and the payload of the first member ready.
+    pub fn wait_signal_or_timeout(
+        self,
+        timeout: Duration,
+    ) -> Result<(String, T), CompositeGateError<E>> {
+        wait_members(self.members, timeout, Progress::any)
+    }
+}
+
+fn named<N: Into<String>, G>(members: impl IntoIterator<Item = (N, G)>) -> Vec<(String, G)> {
+    members
+        .into_iter()
+        .map(|(name, gate)| (name.into(), gate))
+        .collect()
+}
+
+type Decision<R, E> = Option<Result<R, CompositeGateError<E>>>;
+
+fn wait_members<T, E, R>(
+    members: Vec<(String, TimeGate<T, E>)>,
+    timeout: Duration,
+    decide: fn(&mut Progress<T, E>) -> Decision<R, E>,
+) -> Result<R, CompositeGateError<E>> {
+    let stopwatch = Stopwatch::start_new();
+    let bell = Bell::default();
+    let (names, gates): (Vec<_>, Vec<_>) = members.into_iter().unzip();
+    for gate in &gates {
+        gate.watch(bell.clone());
+    }
+
+    let mut progress = Progress::new(names);
+    let (lock, condvar) = &*bell;
+    let mut guard = lock.lock().unwrap();
+
+    loop {
+        progress.collect(|index| gates[index].take_signal());
+        if let Some(decision) = decide(&mut progress) {
+            return decision;
+        }
+
+        let elapsed = stopwatch.elapsed();
+        if elapsed >= timeout {
+            return Err(progress.timeout(timeout));
+        }
+        guard = condvar.wait_timeout(guard, timeout - elapsed).unwrap().0;
+    }
+}
+
+/// The signals received so far by a composite gate.
+struct Progress<T, E> {
+    names: Vec<String>,
+    signals: Vec<Option<Result<T, E>>>,
+}
+
+impl<T, E> Progress<T, E> {
+    fn new(names: Vec<String>) -> Self {
+        let signals = names.iter().map(|_| None).collect();
+        Self { names, signals }
+    }
+
+    fn collect(&mut self, take_signal: impl Fn(usize) -> Option<Result<T, E>>) {
+        for (index, signal) in self.signals.iter_mut().enumerate() {
+            if signal.is_none() {
+                *signal = take_signal(index);
+            }
+        }
+    }
+
+    fn all(&mut self) -> Decision<Vec<T>, E> {
+        if let Some(failure) = self.take_failure() {
+            return Some(Err(failure));
+        }
+        if !self.signals.iter().all(Option::is_some) {
+            return None;
+        }
+
+        let payloads = self.signals.drain(..).filter_map(|signal| signal?.ok());
+        Some(Ok(payloads.collect()))
+    }
+
+    fn any(&mut self) -> Decision<(String, T), E> {
+        let ready = self
+            .signals
+            .iter()
+            .position(|signal| matches!(signal, Some(Ok(_))));
+        if let Some(index) = ready {
+            let payload = self.signals[index].take()?.ok()?;
+            return Some(Ok((self.names[index].clone(), payload)));
+        }
+
+        if self.signals.iter().all(Option::is_some) {
+            return self.take_failure().map(Err);
+        }
+        None
+    }
+
+    fn take_failure(&mut self) -> Option<CompositeGateError<E>> {
+        let index = self
+            .signals
+            .iter()
+            .position(|signal| matches!(signal, Some(Err(_))))?;
+        let error = self.signals[index].take()?.err()?;
+
+        Some(CompositeGateError::Failed {
+            member: self.names[index].clone(),
+            error,
+        })
+    }
+
+    fn timeout(&self, timeout: Duration) -> CompositeGateError<E> {
+        let pending = self
+            .names
+            .iter()
+            .zip(&self.signals)
+            .filter(|(_, signal)| signal.is_none())
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        CompositeGateError::Timeout {
+            timeout: TimeoutError {
+                duration: timeout,
+                ready_checks_interval: None,
+            },
+            pending,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+mod asyncc {
+    use futures::future::select;
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+    use stopwatch::Stopwatch;
+    use tokio::sync::Notify;
+
+    use super::{named, CompositeGateError, CountdownTimeoutError, Decision, Progress};
+    use crate::{
+        helper::{AsyncReadyFn, AsyncTimeGate},
+        runtime::sleep,
+        TimeoutError,
+    };
+
+    /// Same as `CountdownGate` for async setups.
+    pub struct AsyncCountdownGate {
+        remaining: Arc<(Mutex<usize>, Notify)>,
+    }
+
+    impl AsyncCountdownGate {
+        pub fn new(count: usize) -> Self {
+            AsyncCountdownGate {
+                remaining: Arc::new((Mutex::new(count), Notify::new())),
+            }
+        }
+
+        pub fn notifier(&self) -> AsyncReadyFn {
+            let remaining = self.remaining.clone();
+
+            Box::new(move || {
+                let (count, notify) = &*remaining;
+                let mut count = count.lock().unwrap();
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    notify.notify_one();
+                }
+                Box::pin(async {})
+            })
+        }
+
+        pub async fn wait_signal(self) {
+            while self.remaining() > 0 {
+                self.remaining.1.notified().await;
+            }
+        }
+
+        pub async fn wait_signal_or_timeout(
+            self,
+            timeout: Duration,
+        ) -> Result<(), CountdownTimeoutError> {
+            let stopwatch = Stopwatch::start_new();
+
+            loop {
+                let remaining = self.remaining();
+                if remaining == 0 {
+                    return Ok(());
+                }
+
+                let elapsed = stopwatch.elapsed();
+                if elapsed >= timeout {
+                    return Err(CountdownTimeoutError {
+                        timeout: TimeoutError {
+                            duration: timeout,
+                            ready_checks_interval: None,
+                        },
+                        remaining,
+                    });
+                }
+
+                let notified = self.remaining.1.notified();
+                select(Box::pin(notified), Box::pin(sleep(timeout - elapsed))).await;
+            }
+        }
+
+        fn remaining(&self) -> usize {
+            *self.remaining.0.lock().unwrap()
+        }
+    }
+
+    /// Same as `AllGates` for async setups.
+    pub struct AsyncAllGates<T, E> {
+        members: Vec<(String, AsyncTimeGate<T, E>)>,
+    }
+
+    impl<T, E> AsyncAllGates<T, E> {
+        pub fn new<N: Into<String>>(
+            members: impl IntoIterator<Item = (N, AsyncTimeGate<T, E>)>,
+        ) -> Self {
+            AsyncAllGates {
+                members: named(members),
+            }
+        }
+
+        /// Waits every member against the same deadline, the payloads come in the members order.
+        pub async fn wait_signal_or_timeout(
+            self,
+            timeout: Duration,
+        ) -> Result<Vec<T>, CompositeGateError<E>> {
+            wait_members(self.members, timeout, Progress::all).await
+        }
+    }
+
+    /// Same as `AnyGates` for async setups.
+    pub struct AsyncAnyGates<T, E> {
+        members: Vec<(String, AsyncTimeGate<T, E>)>,
+    }
+
+    impl<T, E> AsyncAnyGates<T, E> {
+        pub fn new<N: Into<String>>(
+            members: impl IntoIterator<Item = (N, AsyncTimeGate<T, E>)>,
+        ) -> Self {
+            AsyncAnyGates {
+                members: named(members),
+            }
+        }
+
+        /// Gives the name and the payload of the first member ready.
+        pub async fn wait_signal_or_timeout(
+            self,
+            timeout: Duration,
+        ) -> Result<(String, T), CompositeGateError<E>> {
+            wait_members(self.members, timeout, Progress::any).await
+        }
+    }
+
+    async fn wait_members<T, E, R>(
+        members: Vec<(String, AsyncTimeGate<T, E>)>,
+        timeout: Duration,
+        decide: fn(&mut Progress<T, E>) -> Decision<R, E>,
+    ) -> Result<R, CompositeGateError<E>> {
+        let stopwatch = Stopwatch::start_new();
+        let bell = Arc::new(Notify::new());
+        let (names, gates): (Vec<_>, Vec<_>) = members.into_iter().unzip();
+        for gate in &gates {
+            gate.watch(bell.clone());
+        }
+
+        let mut progress = Progress::new(names);
+
+        loop {
+            progress.collect(|index| gates[index].take_signal());
+            if let Some(decision) = decide(&mut progress) {
+                return decision;
+            }
+
+            let elapsed = stopwatch.elapsed();
+            if elapsed >= timeout {
+                return Err(progress.timeout(timeout));
+            }
+            select(
+                Box::pin(bell.notified()),
+                Box::pin(sleep(timeout - elapsed)),
+            )
+            .await;
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use std::time::Duration;
+        use tokio::{spawn, time::sleep};
+
+        use super::{AsyncAllGates, AsyncAnyGates, AsyncCountdownGate};
+        use crate::helper::{AsyncTimeGate, CompositeGateError};
+
+        #[tokio::test]
+        async fn it_waits_every_countdown_signal() {
+            let gate = AsyncCountdownGate::new(3);
+
+            for delay in [10, 20, 30] {
+                let ready = gate.notifier();
+                spawn(async move {
+                    sleep(Duration::from_millis(delay)).await;
+                    ready().await;
+                });
+            }
+
+            assert!(gate
+                .wait_signal_or_timeout(Duration::from_millis(100))
+                .await
+                .is_ok());
+        }
+
+        #[tokio::test]
+        async fn it_waits_all_gates() {
+            let (first, second) = (AsyncTimeGate::with_payload(), AsyncTimeGate::with_payload());
+            let (first_sender, second_sender) = (first.sender(), second.sender());
+
+            spawn(async move {
+                sleep(Duration::from_millis(20)).await;
+                second_sender.ready(2);
+                sleep(Duration::from_millis(20)).await;
+                first_sender.ready(1);
+            });
+
+            let all = AsyncAllGates::<u16, String>::new([("first", first), ("second", second)]);
+            assert_eq!(
+                all.wait_signal_or_timeout(Duration::from_millis(100)).await,
+                Ok(vec![1, 2])
+            );
+        }
+
+        #[tokio::test]
+        async fn it_reports_pending_gates() {
+            let (first, second) = (AsyncTimeGate::event_driven(), AsyncTimeGate::event_driven());
+            first.notifier()().await;
+
+            let any = AsyncAnyGates::new([("first", first), ("second", second)]);
+            assert_eq!(
+                any.wait_signal_or_timeout(Duration::from_millis(10)).await,
+                Ok(("first".to_owned(), ()))
+            );
+
+            let (first, second) = (AsyncTimeGate::event_driven(), AsyncTimeGate::event_driven());
+            first.notifier()().await;
+
+            let all = AsyncAllGates::new([("first", first), ("second", second)]);
+            assert!(matches!(
+                all.wait_signal_or_timeout(Duration::from_millis(10)).await,
+                Err(CompositeGateError::Timeout { pending, .. }) if pending == ["second"]
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        thread::{sleep, spawn},
+        time::Duration,
+    };
+    use stopwatch::Stopwatch;
+
+    use super::{AllGates, AnyGates, CompositeGateError, CountdownGate, CountdownTimeoutError};
+    use crate::{helper::TimeGate, TimeoutError};
+
+    #[test]
+    fn it_waits_every_countdown_signal() {
+        let stopwatch = Stopwatch::start_new();
+        let gate = CountdownGate::new(3);
+
+        for delay in [30, 60, 100] {
+            let ready = gate.notifier();
+            spawn(move || {
+                sleep(Duration::from_millis(delay));
+                ready();
+            });
+        }
+
+        gate.wait_signal();
+        let ms = stopwatch.elapsed_ms();
+        assert!((100..115).contains(&ms), "stopwatch has {} elapsed ms", ms);
+    }
+
+    #[test]
+    fn it_reports_the_remaining_countdown_signals() {
+        let gate = CountdownGate::new(3);
+        gate.notifier()();
+
+        assert_eq!(
+            gate.wait_signal_or_timeout(Duration::from_millis(10)),
+            Err(CountdownTimeoutError {
+                timeout: TimeoutError {
+                    duration: Duration::from_millis(10),
+                    ready_checks_interval: None,
+                },
+                remaining: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn it_waits_all_gates_with_a_shared_deadline() {
+        let stopwatch = Stopwatch::start_new();
+        let gates = (0..3).map(|_| TimeGate::with_payload()).collect::<Vec<_>>();
+
+        for (delay, gate) in [100, 30, 60].into_iter().zip(&gates) {
+            let sender = gate.sender();
+            spawn(move || {
+                sleep(Duration::from_millis(delay));
+                sender.ready(delay);
+            });
+        }
+
+        let all = AllGates::<u64, String>::new(["a", "b", "c"].into_iter().zip(gates));
+        assert_eq!(
+            all.wait_signal_or_timeout(Duration::from_millis(115)),
+            Ok(vec![100, 30, 60])
+        );
+        let ms = stopwatch.elapsed_ms();
+        assert!((100..115).contains(&ms), "stopwatch has {} elapsed ms", ms);
+    }
+
+    #[test]
+    fn it_fails_all_gates_as_soon_as_one_fails() {
+        let (worker, db) = (TimeGate::with_payload(), TimeGate::with_payload());
+        db.sender().fail("failed to bind");
+
+        let all = AllGates::<(), &str>::new([("worker", worker), ("db", db)]);
+        assert_eq!(
+            all.wait_signal_or_timeout(Duration::from_millis(100)),
+            Err(CompositeGateError::Failed {
+                member: "db".to_owned(),
+                error: "failed to bind",
+            })
+        );
+    }
+
+    #[test]
+    fn it_reports_pending_gates() {
+        let (first, second, third) = (
+            TimeGate::event_driven(),
+            TimeGate::event_driven(),
+            TimeGate::event_driven(),
+        );
+        second.notifier()();
+
+        let all = AllGates::new([("first", first), ("second", second), ("third", third)]);
+        assert_eq!(
+            all.wait_signal_or_timeout(Duration::from_millis(20)),
+            Err(CompositeGateError::Timeout {
+                timeout: TimeoutError {
+                    duration: Duration::from_millis(20),
+                    ready_checks_interval: None,
+                },
+                pending: vec!["first".to_owned(), "third".to_owned()],
+            })
+        );
+    }
+
+    #[test]
+    fn it_opens_any_gate_with_the_first_ready() {
+        let (failing, slow, fast) = (
+            TimeGate::with_payload(),
+            TimeGate::with_payload(),
+            TimeGate::with_payload(),
+        );
+        failing.sender().fail("failed to bind");
+        let (slow_sender, fast_sender) = (slow.sender(), fast.sender());
+        spawn(move || {
+            sleep(Duration::from_millis(20));
+            fast_sender.ready(2);
+            sleep(Duration::from_millis(20));
+            slow_sender.ready(1);
+        });
+
+        let any =
+            AnyGates::<u16, &str>::new([("failing", failing), ("slow", slow), ("fast", fast)]);
+        assert_eq!(
+            any.wait_signal_or_timeout(Duration::from_millis(100)),
+            Ok(("fast".to_owned(), 2))
+        );
+    }
+}
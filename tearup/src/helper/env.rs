@@ -0,0 +1,265 @@
+use std::{ffi::OsString, marker::PhantomData, path::PathBuf};
+
+use crate::{locks::KeyLocks, Context, SharedContext};
+
+static ENV_LOCKS: KeyLocks = KeyLocks::new();
+static CWD_LOCK: KeyLocks = KeyLocks::new();
+
+/// The variables set by `EnvContext`.
+pub trait EnvSpec: Send + Sync + 'static {
+    /// `None` removes the variable.
+    fn vars() -> Vec<(String, Option<String>)>;
+}
+
+/// Sets `S::vars()` for the test and restores the previous values at teardown, see `EnvGuard`.
+///
+/// Use `Async<EnvContext<S>>` in async tests.
+pub struct EnvContext<S: EnvSpec> {
+    _guard: EnvGuard,
+    _spec: PhantomData<S>,
+}
+
+impl<S: EnvSpec> Context for EnvContext<S> {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self {
+            _guard: EnvGuard::acquire(S::vars()),
+            _spec: PhantomData,
+        }
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+}
+
+/// Environment variables set while the guard lives, the previous values are restored on drop.
+/// Tests touching the same variables wait for each other, so a test must not set a variable twice.
+pub struct EnvGuard {
+    keys: Vec<String>,
+    previous: Vec<(String, Option<OsString>)>,
+}
+
+impl EnvGuard {
+    /// Wait for the variables to be free, then set them, `None` removes the variable.
+    pub fn acquire<K: Into<String>, V: Into<OsString>>(
+        vars: impl IntoIterator<Item = (K, Option<V>)>,
+    ) -> Self {
+        let vars = vars
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.map(Into::into)))
+            .collect::<Vec<(String, Option<OsString>)>>();
+
+        let mut keys = vars.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        ENV_LOCKS.lock(&keys);
+
+        // Built first so that a panic below restores what was set and releases the lock
+        let mut guard = Self {
+            keys,
+            previous: vec![],
+        };
+        for (key, value) in vars {
+            if !guard.previous.iter().any(|(previous, _)| *previous == key) {
+                guard.previous.push((key.clone(), std::env::var_os(&key)));
+            }
+            match value {
+                Some(value) => std::env::set_var(&key, value),
+                None => std::env::remove_var(&key),
+            }
+        }
+        guard
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (key, value) in self.previous.drain(..).rev() {
+            match value {
+                Some(value) => std::env::set_var(&key, value),
+                None => std::env::remove_var(&key),
+            }
+        }
+        ENV_LOCKS.unlock(&self.keys);
+    }
+}
+
+/// The working directory set by `CwdContext`.
+pub trait CwdSpec: Send + Sync + 'static {
+    /// Relative to the crate root.
+    fn dir() -> PathBuf;
+}
+
+/// Moves to `S::dir()` for the test and back at teardown, see `CwdGuard`.
+///
+/// Use `Async<CwdContext<S>>` in async tests.
+pub struct CwdContext<S: CwdSpec> {
+    _guard: CwdGuard,
+    _spec: PhantomData<S>,
+}
+
+impl<S: CwdSpec> Context for CwdContext<S> {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self {
+            _guard: CwdGuard::acquire(S::dir()),
+            _spec: PhantomData,
+        }
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+}
+
+/// Working directory set while the guard lives, the previous one is restored on drop.
+/// Tests changing it run one at a time.
+pub struct CwdGuard {
+    previous: Option<PathBuf>,
+}
+
+const CWD_KEY: &str = "cwd";
+
+impl CwdGuard {
+    /// Wait for the other tests to restore their directory, then move to `dir`, relative to the crate root.
+    pub fn acquire(dir: impl Into<PathBuf>) -> Self {
+        let dir = super::crate_root().join(dir.into());
+        CWD_LOCK.lock(&[CWD_KEY]);
+
+        // Built first so that a panic below releases the lock
+        let mut guard = Self { previous: None };
+        let previous = std::env::current_dir().expect("Failed to read the working directory");
+        guard.previous = Some(previous);
+        if let Err(err) = std::env::set_current_dir(&dir) {
+            panic!("Failed to move to {}: {}", dir.display(), err);
+        }
+        guard
+    }
+}
+
+impl Drop for CwdGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            if let Err(err) = std::env::set_current_dir(&previous) {
+                eprintln!("Failed to move back to {}: {}", previous.display(), err);
+            }
+        }
+        CWD_LOCK.unlock(&[CWD_KEY]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        path::PathBuf,
+        process::Command,
+        sync::{Arc, Barrier},
+        thread::{sleep, spawn},
+        time::Duration,
+    };
+    use stopwatch::Stopwatch;
+
+    use super::{CwdContext, CwdSpec, EnvContext, EnvGuard, EnvSpec};
+    use crate::{Context, SharedContext};
+
+    #[test]
+    fn it_sets_and_restores_variables() {
+        std::env::set_var("TEARUP_ENV_TEST_KEPT", "before");
+        std::env::remove_var("TEARUP_ENV_TEST_ADDED");
+
+        let guard = EnvGuard::acquire([
+            ("TEARUP_ENV_TEST_KEPT", None),
+            ("TEARUP_ENV_TEST_ADDED", Some("first")),
+            ("TEARUP_ENV_TEST_ADDED", Some("second")),
+        ]);
+        assert_eq!(std::env::var_os("TEARUP_ENV_TEST_KEPT"), None);
+        assert_eq!(std::env::var("TEARUP_ENV_TEST_ADDED").unwrap(), "second");
+
+        drop(guard);
+        assert_eq!(std::env::var("TEARUP_ENV_TEST_KEPT").unwrap(), "before");
+        assert_eq!(std::env::var_os("TEARUP_ENV_TEST_ADDED"), None);
+    }
+
+    #[test]
+    fn it_restores_variables_on_panic() {
+        let result = std::panic::catch_unwind(|| {
+            let _guard = EnvGuard::acquire([("TEARUP_ENV_TEST_PANIC", Some("set"))]);
+            panic!("boom");
+        });
+
+        assert!(result.is_err());
+        assert_eq!(std::env::var_os("TEARUP_ENV_TEST_PANIC"), None);
+    }
+
+    #[test]
+    fn it_runs_conflicting_guards_one_at_a_time() {
+        let guard = EnvGuard::acquire([("TEARUP_ENV_TEST_SHARED", Some("first"))]);
+        let barrier = Arc::new(Barrier::new(2));
+
+        let waiting = barrier.clone();
+        let second = spawn(move || {
+            waiting.wait();
+            let stopwatch = Stopwatch::start_new();
+            let _guard = EnvGuard::acquire([("TEARUP_ENV_TEST_SHARED", Some("second"))]);
+            stopwatch.elapsed_ms()
+        });
+        // Other variables stay available
+        let other = EnvGuard::acquire([("TEARUP_ENV_TEST_OTHER", Some("other"))]);
+        drop(other);
+
+        barrier.wait();
+        sleep(Duration::from_millis(100));
+        assert_eq!(std::env::var("TEARUP_ENV_TEST_SHARED").unwrap(), "first");
+        drop(guard);
+
+        assert!(second.join().unwrap() >= 90);
+        assert_eq!(std::env::var_os("TEARUP_ENV_TEST_SHARED"), None);
+    }
+
+    struct Vars;
+    impl EnvSpec for Vars {
+        fn vars() -> Vec<(String, Option<String>)> {
+            vec![("TEARUP_ENV_TEST_CONTEXT".into(), Some("context".into()))]
+        }
+    }
+
+    #[test]
+    fn it_sets_variables_until_teardown() {
+        let mut shared_context = SharedContext::default();
+        let context = EnvContext::<Vars>::setup(&mut shared_context);
+        assert_eq!(std::env::var("TEARUP_ENV_TEST_CONTEXT").unwrap(), "context");
+
+        context.teardown(&mut shared_context);
+        assert_eq!(std::env::var_os("TEARUP_ENV_TEST_CONTEXT"), None);
+    }
+
+    struct Sources;
+    impl CwdSpec for Sources {
+        fn dir() -> PathBuf {
+            "src".into()
+        }
+    }
+
+    /// Set for the child of `it_moves_until_teardown`.
+    const CWD_CHILD: &str = "TEARUP_CWD_CHILD";
+
+    /// The working directory is the one of the whole test binary: moved in a child running only this test.
+    #[test]
+    fn it_moves_until_teardown() {
+        if std::env::var_os(CWD_CHILD).is_none() {
+            let child = Command::new(std::env::current_exe().unwrap())
+                .args(["helper::env::test::it_moves_until_teardown", "--exact"])
+                .env(CWD_CHILD, "1")
+                .output()
+                .unwrap();
+            assert!(
+                child.status.success(),
+                "{}",
+                String::from_utf8_lossy(&child.stdout)
+            );
+            return;
+        }
+
+        let mut shared_context = SharedContext::default();
+        let context = CwdContext::<Sources>::setup(&mut shared_context);
+        assert!(std::env::current_dir().unwrap().ends_with("src"));
+
+        context.teardown(&mut shared_context);
+        assert!(!std::env::current_dir().unwrap().ends_with("src"));
+    }
+}
@@ -1,4 +1,72 @@
+mod composite_gate;
+pub use composite_gate::*;
+mod env;
+pub use env::*;
+pub mod ports;
+pub mod probe;
+pub mod process;
 mod ready_when;
 pub use ready_when::*;
+mod temp_dir;
+pub use temp_dir::*;
 mod time_gate;
 pub use time_gate::*;
+
+/// Where relative paths of the helpers start from.
+fn crate_root() -> std::path::PathBuf {
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_default()
+}
+
+/// Where the reports are written: `tearup` in the target directory of cargo,
+/// found from `CARGO_TARGET_DIR` or the path of the test binary, `fallback_tearup_dir()` otherwise.
+pub(crate) fn tearup_dir() -> std::path::PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            let exe = std::env::current_exe().ok()?;
+            let target = exe.ancestors().find(|dir| dir.ends_with("target"))?;
+            Some(target.to_owned())
+        })
+        .map(|target| target.join("tearup"))
+        .unwrap_or_else(fallback_tearup_dir)
+}
+
+/// `tearup` in the temporary directory, for a target directory not named `target`.
+pub(crate) fn fallback_tearup_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join("tearup")
+}
+
+/// A number of seconds read from the environment, a value that isn't one is ignored with a warning.
+pub(crate) fn secs_var(key: &str) -> Option<std::time::Duration> {
+    let secs = std::env::var(key).ok()?;
+    match secs.parse().map(std::time::Duration::try_from_secs_f64) {
+        Ok(Ok(duration)) => Some(duration),
+        _ => {
+            eprintln!("Ignoring {}={}: not a number of seconds", key, secs);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::secs_var;
+
+    #[test]
+    fn it_ignores_what_is_not_a_number_of_seconds() {
+        let key = "TEARUP_TEST_SECS_VAR";
+        for secs in ["-1", "inf", "NaN", "1e30", "soon"] {
+            std::env::set_var(key, secs);
+            assert_eq!(secs_var(key), None, "{}", secs);
+        }
+
+        std::env::set_var(key, "0.5");
+        assert_eq!(secs_var(key), Some(Duration::from_millis(500)));
+        std::env::remove_var(key);
+        assert_eq!(secs_var(key), None);
+    }
+}
@@ -0,0 +1,206 @@
+//! Free ports reserved across parallel tests and test binaries.
+//!
+//! A port is reserved with a lock file under `<temp dir>/tearup-ports`, held until release.
+//! On unix the lock goes away with the process, elsewhere a crashed test leaves the port reserved.
+//!
+//! ```ignore
+//! #[tearup_test(PortContext)]
+//! fn it_serves(port: ReservedPort) {
+//!     let server = launch_server(port.port());
+//! }
+//! ```
+use std::{
+    fs, io,
+    net::{Ipv4Addr, SocketAddr, TcpListener},
+    sync::{Arc, Mutex},
+};
+
+use crate::{locks::FileLock, Context, SharedContext};
+
+/// Attempts to find a port nobody else reserved before giving up.
+const MAX_ATTEMPTS: usize = 100;
+
+/// A port kept for a test until `release()`, or until every clone is dropped.
+#[derive(Clone)]
+pub struct ReservedPort {
+    port: u16,
+    reservation: Arc<Mutex<Reservation>>,
+}
+
+struct Reservation {
+    lock: Option<PortLock>,
+    listener: Option<TcpListener>,
+}
+
+impl ReservedPort {
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// `127.0.0.1:<port>`
+    pub fn address(&self) -> SocketAddr {
+        (Ipv4Addr::LOCALHOST, self.port).into()
+    }
+
+    /// The listener already bound to the port, only for `reserve_bound_port` and once.
+    pub fn take_listener(&self) -> Option<TcpListener> {
+        self.reservation.lock().unwrap().listener.take()
+    }
+
+    /// Let other tests use the port.
+    pub fn release(&self) {
+        let mut reservation = self.reservation.lock().unwrap();
+        reservation.listener = None;
+        reservation.lock = None;
+    }
+}
+
+/// Reserve a free port, nothing listens on it: bind it yourself.
+pub fn reserve_port() -> io::Result<ReservedPort> {
+    let port = reserve_bound_port()?;
+    port.take_listener();
+    Ok(port)
+}
+
+/// Reserve a free port and keep it bound, hand the listener over with `take_listener()`.
+pub fn reserve_bound_port() -> io::Result<ReservedPort> {
+    for _ in 0..MAX_ATTEMPTS {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let port = listener.local_addr()?.port();
+
+        if let Some(lock) = PortLock::try_acquire(port)? {
+            return Ok(ReservedPort {
+                port,
+                reservation: Arc::new(Mutex::new(Reservation {
+                    lock: Some(lock),
+                    listener: Some(listener),
+                })),
+            });
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::AddrInUse,
+        format!("No free port after {} attempts", MAX_ATTEMPTS),
+    ))
+}
+
+/// Registers a `ReservedPort` for the test and releases it at teardown.
+pub struct PortContext(ReservedPort);
+
+impl Context for PortContext {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        let port = reserve_port().expect("Failed to reserve a port");
+        shared_context.register(port.clone());
+        Self(port)
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {
+        self.0.release();
+    }
+}
+
+/// Same as `PortContext` with the port kept bound, see `ReservedPort::take_listener`.
+pub struct BoundPortContext(ReservedPort);
+
+impl Context for BoundPortContext {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        let port = reserve_bound_port().expect("Failed to reserve a port");
+        shared_context.register(port.clone());
+        Self(port)
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {
+        self.0.release();
+    }
+}
+
+/// The lock file of a port, unlocked on drop.
+struct PortLock {
+    _lock: FileLock,
+}
+
+impl PortLock {
+    fn try_acquire(port: u16) -> io::Result<Option<Self>> {
+        let dir = std::env::temp_dir().join("tearup-ports");
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.lock", port));
+
+        Ok(FileLock::try_acquire(&path)?.map(|lock| Self { _lock: lock }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::HashSet,
+        io::{Read, Write},
+        net::TcpStream,
+        thread::spawn,
+    };
+
+    use super::{reserve_bound_port, reserve_port, BoundPortContext, PortLock, ReservedPort};
+    use crate::{Context, SharedContext};
+
+    #[test]
+    fn it_reserves_distinct_ports() {
+        let ports = (0..20).map(|_| reserve_port().unwrap()).collect::<Vec<_>>();
+
+        let distinct = ports.iter().map(ReservedPort::port).collect::<HashSet<_>>();
+        assert_eq!(distinct.len(), ports.len());
+    }
+
+    #[test]
+    fn it_holds_the_lock_until_release() {
+        let port = reserve_port().unwrap();
+        assert!(PortLock::try_acquire(port.port()).unwrap().is_none());
+
+        let clone = port.clone();
+        port.release();
+
+        assert!(PortLock::try_acquire(clone.port()).unwrap().is_some());
+    }
+
+    #[test]
+    fn it_releases_when_dropped() {
+        let port = reserve_port().unwrap();
+        let number = port.port();
+
+        drop(port);
+
+        assert!(PortLock::try_acquire(number).unwrap().is_some());
+    }
+
+    #[test]
+    fn it_hands_over_the_bound_listener() {
+        let port = reserve_bound_port().unwrap();
+        let listener = port.take_listener().unwrap();
+        assert!(port.take_listener().is_none());
+
+        spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"hello").unwrap();
+        });
+
+        let mut hello = String::new();
+        TcpStream::connect(port.address())
+            .unwrap()
+            .read_to_string(&mut hello)
+            .unwrap();
+        assert_eq!(hello, "hello");
+    }
+
+    #[test]
+    fn it_registers_the_port_until_teardown() {
+        let mut shared_context = SharedContext::default();
+        let context = BoundPortContext::setup(&mut shared_context);
+
+        let port = shared_context.get::<ReservedPort>().unwrap();
+        assert!(port.take_listener().is_some());
+        assert!(PortLock::try_acquire(port.port()).unwrap().is_none());
+
+        context.teardown(&mut shared_context);
+
+        assert!(PortLock::try_acquire(port.port()).unwrap().is_some());
+    }
+}
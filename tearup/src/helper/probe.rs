@@ -0,0 +1,421 @@
+//! Readiness probes to plug into `ready_when_or_timeout`.
+//!
+//! ```ignore
+//! ready_when_or_timeout(
+//!     probe::tcp("localhost:8080"),
+//!     Backoff::fixed(Duration::from_millis(10)),
+//!     Duration::from_secs(1),
+//! )
+//! .unwrap();
+//! ```
+#[cfg(feature = "async")]
+pub use asyncc::*;
+#[cfg(feature = "regex")]
+pub use matches::*;
+use std::{
+    fmt::{self, Display},
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    time::Duration,
+};
+
+pub type ProbeFn = Box<dyn FnMut() -> Result<(), ProbeFailure> + Send>;
+
+/// Time given to a single connection attempt.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Why a probe is not ready yet, e.g. `tcp localhost:8080: Connection refused`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProbeFailure {
+    pub probe: String,
+    pub reason: String,
+}
+
+impl Display for ProbeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.probe, self.reason)
+    }
+}
+
+impl std::error::Error for ProbeFailure {}
+
+fn failure(probe: &str, reason: impl ToString) -> ProbeFailure {
+    ProbeFailure {
+        probe: probe.to_owned(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Ready once a TCP connection to `address` succeeds.
+pub fn tcp(address: impl Into<String>) -> ProbeFn {
+    let address = address.into();
+    let probe = format!("tcp {}", address);
+
+    Box::new(move || {
+        let addrs = address
+            .to_socket_addrs()
+            .map_err(|err| failure(&probe, err))?;
+
+        let mut last_error = failure(&probe, "no address resolved");
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(_) => return Ok(()),
+                Err(err) => last_error = failure(&probe, err),
+            }
+        }
+        Err(last_error)
+    })
+}
+
+/// Ready once a GET on `url` answers with `status`.
+#[cfg(feature = "http")]
+pub fn http_status(url: impl Into<String>, status: u16) -> ProbeFn {
+    let url = url.into();
+    let probe = format!("http GET {}", url);
+
+    Box::new(move || {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(CONNECT_TIMEOUT)
+            .build()
+            .map_err(|err| failure(&probe, err))?;
+        let response = client
+            .get(&url)
+            .send()
+            .map_err(|err| failure(&probe, err))?;
+
+        match response.status().as_u16() {
+            actual if actual == status => Ok(()),
+            actual => Err(failure(
+                &probe,
+                format!("expected status {}, got {}", status, actual),
+            )),
+        }
+    })
+}
+
+/// Ready once a connection to the Unix socket at `path` succeeds.
+#[cfg(unix)]
+pub fn unix_socket(path: impl Into<PathBuf>) -> ProbeFn {
+    let path = path.into();
+    let probe = format!("unix socket {}", path.display());
+
+    Box::new(move || {
+        std::os::unix::net::UnixStream::connect(&path)
+            .map(|_| ())
+            .map_err(|err| failure(&probe, err))
+    })
+}
+
+/// Ready once `path` exists.
+pub fn path_exists(path: impl Into<PathBuf>) -> ProbeFn {
+    let path = path.into();
+    let probe = format!("path {}", path.display());
+
+    Box::new(move || {
+        if path.exists() {
+            Ok(())
+        } else {
+            Err(failure(&probe, "does not exist"))
+        }
+    })
+}
+
+#[cfg(feature = "regex")]
+mod matches {
+    use regex::Regex;
+    use std::{
+        io::{BufRead, BufReader, Read},
+        path::PathBuf,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use super::{failure, ProbeFn};
+
+    /// Ready once the content of the file at `path` matches `pattern`.
+    pub fn file_matches(path: impl Into<PathBuf>, pattern: Regex) -> ProbeFn {
+        let path = path.into();
+        let probe = format!("file {}", path.display());
+
+        Box::new(move || {
+            let content = std::fs::read_to_string(&path).map_err(|err| failure(&probe, err))?;
+
+            if pattern.is_match(&content) {
+                Ok(())
+            } else {
+                Err(failure(&probe, format!("no match for /{}/", pattern)))
+            }
+        })
+    }
+
+    /// Ready once a line of `output`, e.g. the stdout of a child process, matches `pattern`.
+    /// The output is read in the background so the probe never blocks.
+    pub fn output_matches(output: impl Read + Send + 'static, pattern: Regex) -> ProbeFn {
+        let probe = format!("output /{}/", pattern);
+        let state = Arc::new(Mutex::new(OutputState::default()));
+
+        let reader_state = state.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines() {
+                let mut state = reader_state.lock().unwrap();
+                match line {
+                    Ok(line) => {
+                        state.matched |= pattern.is_match(&line);
+                        state.last_line = Some(line);
+                    }
+                    Err(err) => {
+                        state.closed = Some(err.to_string());
+                        return;
+                    }
+                }
+            }
+            reader_state.lock().unwrap().closed = Some("output closed".to_owned());
+        });
+
+        Box::new(move || {
+            let state = state.lock().unwrap();
+            if state.matched {
+                return Ok(());
+            }
+
+            let last_line = match &state.last_line {
+                Some(line) => format!("last line '{}'", line),
+                None => "nothing written".to_owned(),
+            };
+            let reason = match &state.closed {
+                Some(closed) => format!("{}, {}", closed, last_line),
+                None => format!("no match yet, {}", last_line),
+            };
+            Err(failure(&probe, reason))
+        })
+    }
+
+    #[derive(Default)]
+    struct OutputState {
+        matched: bool,
+        last_line: Option<String>,
+        closed: Option<String>,
+    }
+
+    #[cfg(test)]
+    mod test {
+        use regex::Regex;
+        use std::{
+            process::{Command, Stdio},
+            time::Duration,
+        };
+
+        use super::{file_matches, output_matches};
+        use crate::helper::{ready_when_or_timeout, Backoff};
+
+        #[test]
+        fn it_probes_files() {
+            let path =
+                std::env::temp_dir().join(format!("tearup-probe-file-{}", std::process::id()));
+            let mut matches = file_matches(&path, Regex::new("ready on \\d+").unwrap());
+
+            std::fs::write(&path, "starting").unwrap();
+            assert_eq!(
+                matches().unwrap_err().reason,
+                "no match for /ready on \\d+/".to_owned()
+            );
+
+            std::fs::write(&path, "starting\nready on 8080").unwrap();
+            assert_eq!(matches(), Ok(()));
+            std::fs::remove_file(path).unwrap();
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn it_probes_process_output() {
+            let mut child = Command::new("sh")
+                .args(["-c", "echo starting; sleep 0.05; echo listening on 8080"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap();
+            let output = child.stdout.take().unwrap();
+
+            let ready = ready_when_or_timeout(
+                output_matches(output, Regex::new("listening on \\d+").unwrap()),
+                Backoff::fixed(Duration::from_millis(10)),
+                Duration::from_millis(500),
+            );
+
+            assert!(ready.is_ok());
+            child.wait().unwrap();
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn it_reports_the_last_line_of_the_process_output() {
+            let mut child = Command::new("sh")
+                .args(["-c", "echo failed to bind"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap();
+            let output = child.stdout.take().unwrap();
+            child.wait().unwrap();
+
+            let err = ready_when_or_timeout(
+                output_matches(output, Regex::new("listening").unwrap()),
+                Backoff::fixed(Duration::from_millis(10)),
+                Duration::from_millis(50),
+            )
+            .unwrap_err();
+
+            assert_eq!(
+                err.last_error.to_string(),
+                "output /listening/: output closed, last line 'failed to bind'"
+            );
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+mod asyncc {
+    use futures::future::BoxFuture;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll},
+    };
+
+    use super::{ProbeFailure, ProbeFn};
+    use crate::runtime::spawn_blocking;
+
+    pub type AsyncProbeFn = Box<dyn FnMut() -> ProbeAttempt + Send>;
+
+    /// One attempt of an `async_probe`, running on the blocking pool of the runtime.
+    pub struct ProbeAttempt(BoxFuture<'static, Result<(), ProbeFailure>>);
+
+    impl Future for ProbeAttempt {
+        type Output = Result<(), ProbeFailure>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            self.0.as_mut().poll(cx)
+        }
+    }
+
+    /// Runs a probe on the blocking pool of the runtime to use it with `async_ready_when_or_timeout`.
+    ///
+    /// ```ignore
+    /// async_ready_when_or_timeout(async_probe(probe::tcp("localhost:8080")), backoff, timeout).await
+    /// ```
+    pub fn async_probe(probe: ProbeFn) -> AsyncProbeFn {
+        let probe = Arc::new(Mutex::new(probe));
+
+        Box::new(move || {
+            let probe = probe.clone();
+            ProbeAttempt(Box::pin(spawn_blocking(move || {
+                let mut probe = probe.lock().unwrap();
+                probe()
+            })))
+        })
+    }
+
+    #[cfg(test)]
+    mod test {
+        use std::{net::TcpListener, time::Duration};
+
+        use super::async_probe;
+        use crate::helper::{async_ready_when_or_timeout, probe, Backoff};
+
+        #[tokio::test]
+        async fn it_probes_in_async_setups() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+
+            let ready = async_ready_when_or_timeout(
+                async_probe(probe::tcp(address)),
+                Backoff::fixed(Duration::from_millis(10)),
+                Duration::from_millis(100),
+            )
+            .await;
+
+            assert!(ready.is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::TcpListener;
+
+    use super::{path_exists, tcp, ProbeFailure};
+
+    #[test]
+    fn it_probes_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut probe = tcp(address.clone());
+
+        assert_eq!(probe(), Ok(()));
+
+        drop(listener);
+        let failure = probe().unwrap_err();
+        assert_eq!(failure.probe, format!("tcp {}", address));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn it_probes_http_status() {
+        use std::io::{Read, Write};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/health", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for status in ["503 Service Unavailable", "200 OK"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let _request = stream.read(&mut [0; 1024]).unwrap();
+                let response = format!("HTTP/1.1 {}\r\ncontent-length: 0\r\n\r\n", status);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        let mut probe = super::http_status(&url, 200);
+
+        assert_eq!(
+            probe(),
+            Err(ProbeFailure {
+                probe: format!("http GET {}", url),
+                reason: "expected status 200, got 503".to_owned(),
+            })
+        );
+        assert_eq!(probe(), Ok(()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_probes_unix_socket() {
+        let path = temp_path("socket");
+        let mut probe = super::unix_socket(&path);
+
+        assert!(probe().is_err());
+
+        let _listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        assert_eq!(probe(), Ok(()));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn it_probes_paths() {
+        let path = temp_path("path");
+        let mut exists = path_exists(&path);
+
+        assert_eq!(
+            exists(),
+            Err(ProbeFailure {
+                probe: format!("path {}", path.display()),
+                reason: "does not exist".to_owned(),
+            })
+        );
+
+        std::fs::write(&path, "starting").unwrap();
+        assert_eq!(exists(), Ok(()));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("tearup-probe-{}-{}", name, std::process::id()))
+    }
+}
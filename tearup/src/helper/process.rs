@@ -0,0 +1,468 @@
+//! Child processes killed at teardown, even when the test panics.
+//!
+//! ```ignore
+//! struct MockServer;
+//! impl ProcessSpec for MockServer {
+//!     fn command() -> Command {
+//!         let mut command = Command::new("mock-server");
+//!         command.arg("--port=8080");
+//!         command
+//!     }
+//!
+//!     // With the `regex` feature
+//!     fn ready(logs: &ProcessLogs) -> Option<ProbeFn> {
+//!         Some(logs.probe(Regex::new("listening").unwrap()))
+//!     }
+//! }
+//!
+//! type MockServerContext = ProcessContext<MockServer>;
+//!
+//! #[tearup_test(MockServerContext)]
+//! fn it_calls_the_server(server: ProcessHandle<MockServer>) { ... }
+//! ```
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+    marker::PhantomData,
+    path::PathBuf,
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{Arc, Mutex},
+    thread::{self, sleep, JoinHandle},
+    time::Duration,
+};
+use stopwatch::Stopwatch;
+
+#[cfg(feature = "regex")]
+use super::probe::ProbeFailure;
+use super::{probe::ProbeFn, ready_when_or_timeout, Backoff};
+use crate::{Context, SharedContext, TestOutcome};
+
+/// Time given to the readers to capture the last logs once the process is stopped,
+/// a process that left the group may keep the output open.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// What `ProcessContext` launches.
+pub trait ProcessSpec: Send + Sync + 'static {
+    /// Time given to the `ready` probe.
+    const READY_TIMEOUT: Duration = Duration::from_secs(5);
+    /// Time given to the process to exit after SIGTERM, before SIGKILL.
+    const GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+    fn command() -> Command;
+
+    /// Waited before the test starts.
+    fn ready(_logs: &ProcessLogs) -> Option<ProbeFn> {
+        None
+    }
+
+    /// Also write the logs to this file.
+    fn log_file() -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Spawns `S::command()` at setup and stops it at teardown.
+/// The `ProcessHandle<S>` is available as fixture and the logs are printed when the test fails.
+///
+/// Use `Async<ProcessContext<S>>` in async tests.
+pub struct ProcessContext<S: ProcessSpec> {
+    process: ManagedProcess,
+    program: String,
+    _spec: PhantomData<S>,
+}
+
+impl<S: ProcessSpec> Context for ProcessContext<S> {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        let command = S::command();
+        let program = format!("{:?}", command.get_program());
+        let process = ManagedProcess::spawn(command, S::log_file())
+            .unwrap_or_else(|err| panic!("Failed to spawn {}: {}", program, err));
+
+        if let Some(probe) = S::ready(&process.logs()) {
+            let backoff =
+                Backoff::exponential(Duration::from_millis(10), Duration::from_millis(200));
+            if let Err(err) = ready_when_or_timeout(probe, backoff, S::READY_TIMEOUT) {
+                panic!(
+                    "{} is not ready, {}\n{}",
+                    program,
+                    err,
+                    process.logs().contents()
+                );
+            }
+        }
+
+        shared_context.register(ProcessHandle::<S> {
+            pid: process.pid(),
+            logs: process.logs(),
+            _spec: PhantomData,
+        });
+
+        Self {
+            process,
+            program,
+            _spec: PhantomData,
+        }
+    }
+
+    fn teardown(self, shared_context: &mut SharedContext) {
+        if let Some(TestOutcome::Failed(_)) = shared_context.get() {
+            eprintln!(
+                "---- logs of {} (pid {}) ----\n{}",
+                self.program,
+                self.process.pid(),
+                self.process.logs().contents()
+            );
+        }
+
+        let pid = self.process.pid();
+        if let Err(err) = self.process.stop(S::GRACE_PERIOD) {
+            eprintln!("Failed to stop pid {}: {}", pid, err);
+        }
+    }
+}
+
+/// The process launched by `ProcessContext<S>`.
+pub struct ProcessHandle<S> {
+    pub pid: u32,
+    pub logs: ProcessLogs,
+    _spec: PhantomData<fn() -> S>,
+}
+
+impl<S> Clone for ProcessHandle<S> {
+    fn clone(&self) -> Self {
+        Self {
+            pid: self.pid,
+            logs: self.logs.clone(),
+            _spec: PhantomData,
+        }
+    }
+}
+
+/// Stdout and stderr of a process, line by line as they come.
+#[derive(Clone, Default)]
+pub struct ProcessLogs(Arc<Mutex<String>>);
+
+impl ProcessLogs {
+    pub fn contents(&self) -> String {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Ready once the logs match `pattern`.
+    #[cfg(feature = "regex")]
+    pub fn probe(&self, pattern: Regex) -> ProbeFn {
+        let logs = self.clone();
+
+        Box::new(move || {
+            if pattern.is_match(&logs.contents()) {
+                Ok(())
+            } else {
+                Err(ProbeFailure {
+                    probe: format!("logs /{}/", pattern),
+                    reason: "no match yet".to_owned(),
+                })
+            }
+        })
+    }
+
+    fn push(&self, line: &str) {
+        self.0.lock().unwrap().push_str(line);
+    }
+}
+
+/// A child process spawned in its own process group so its own children are stopped with it.
+/// Killed on drop if not stopped before.
+pub struct ManagedProcess {
+    child: Option<Child>,
+    pid: u32,
+    logs: ProcessLogs,
+    readers: Vec<JoinHandle<()>>,
+}
+
+impl ManagedProcess {
+    /// Spawn with stdout and stderr captured, and also written to `log_file` if any.
+    pub fn spawn(mut command: Command, log_file: Option<PathBuf>) -> io::Result<Self> {
+        let log_file = match log_file {
+            Some(path) => Some(Arc::new(Mutex::new(File::create(path)?))),
+            None => None,
+        };
+
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let logs = ProcessLogs::default();
+        let mut readers = vec![];
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(capture(stdout, logs.clone(), log_file.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(capture(stderr, logs.clone(), log_file));
+        }
+
+        Ok(Self {
+            pid: child.id(),
+            child: Some(child),
+            logs,
+            readers,
+        })
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn logs(&self) -> ProcessLogs {
+        self.logs.clone()
+    }
+
+    /// SIGTERM the process group, then SIGKILL it if still running after `grace_period`.
+    pub fn stop(mut self, grace_period: Duration) -> io::Result<ExitStatus> {
+        let mut child = self.child.take().expect("stopped once");
+        let stopwatch = Stopwatch::start_new();
+
+        terminate(&mut child)?;
+        while !has_exited(&mut child)? && stopwatch.elapsed() < grace_period {
+            sleep(Duration::from_millis(10));
+        }
+        // What is left of the group, before reaping the leader so its group id can't be reused
+        kill(&mut child)?;
+        let status = child.wait()?;
+
+        let stopwatch = Stopwatch::start_new();
+        while !self.readers.iter().all(JoinHandle::is_finished)
+            && stopwatch.elapsed() < DRAIN_TIMEOUT
+        {
+            sleep(Duration::from_millis(10));
+        }
+        // The ones still reading are detached
+        self.readers.clear();
+        Ok(status)
+    }
+}
+
+impl Drop for ManagedProcess {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = kill(&mut child);
+            let _ = child.wait();
+        }
+    }
+}
+
+fn capture(
+    output: impl Read + Send + 'static,
+    logs: ProcessLogs,
+    log_file: Option<Arc<Mutex<File>>>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut output = BufReader::new(output);
+        let mut line = vec![];
+
+        while matches!(output.read_until(b'\n', &mut line), Ok(read) if read > 0) {
+            let text = String::from_utf8_lossy(&line);
+            logs.push(&text);
+            if let Some(file) = &log_file {
+                let _ = file.lock().unwrap().write_all(text.as_bytes());
+            }
+            line.clear();
+        }
+    })
+}
+
+#[cfg(unix)]
+fn terminate(child: &mut Child) -> io::Result<()> {
+    signal_group(child, libc::SIGTERM)
+}
+
+#[cfg(unix)]
+fn kill(child: &mut Child) -> io::Result<()> {
+    signal_group(child, libc::SIGKILL)
+}
+
+/// Without reaping it, its pid and group id stay taken until `wait`.
+#[cfg(unix)]
+fn has_exited(child: &mut Child) -> io::Result<bool> {
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    let options = libc::WEXITED | libc::WNOHANG | libc::WNOWAIT;
+    match unsafe { libc::waitid(libc::P_PID, child.id(), &mut info, options) } {
+        // Left zeroed while it runs
+        0 => Ok(info.si_signo == libc::SIGCHLD),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+#[cfg(unix)]
+fn signal_group(child: &Child, signal: libc::c_int) -> io::Result<()> {
+    // The group id is the pid of its leader, a negative pid targets the whole group
+    match unsafe { libc::kill(-(child.id() as libc::pid_t), signal) } {
+        0 => Ok(()),
+        _ => match io::Error::last_os_error() {
+            err if err.raw_os_error() == Some(libc::ESRCH) => Ok(()),
+            err => Err(err),
+        },
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate(child: &mut Child) -> io::Result<()> {
+    child.kill()
+}
+
+#[cfg(not(unix))]
+fn has_exited(child: &mut Child) -> io::Result<bool> {
+    Ok(child.try_wait()?.is_some())
+}
+
+#[cfg(not(unix))]
+fn kill(child: &mut Child) -> io::Result<()> {
+    match child.try_wait()? {
+        Some(_) => Ok(()),
+        None => child.kill(),
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use std::{process::Command, time::Duration};
+    use stopwatch::Stopwatch;
+
+    use super::{ManagedProcess, ProcessContext, ProcessHandle, ProcessLogs, ProcessSpec};
+    use crate::{
+        helper::{
+            probe::{ProbeFailure, ProbeFn},
+            ready_when_or_timeout, Backoff,
+        },
+        Context, SharedContext, TestOutcome,
+    };
+
+    fn sh(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.args(["-c", script]);
+        command
+    }
+
+    /// `ProcessLogs::probe` needs the `regex` feature.
+    fn logs_contain(logs: &ProcessLogs, text: &'static str) -> ProbeFn {
+        let logs = logs.clone();
+        Box::new(move || {
+            if logs.contents().contains(text) {
+                Ok(())
+            } else {
+                Err(ProbeFailure {
+                    probe: format!("logs {}", text),
+                    reason: "no match yet".to_owned(),
+                })
+            }
+        })
+    }
+
+    /// Zombies do not count: killed grandchildren wait for init to reap them.
+    fn group_is_alive(pgid: u32) -> bool {
+        std::fs::read_dir("/proc").unwrap().flatten().any(|entry| {
+            let stat = std::fs::read_to_string(entry.path().join("stat")).unwrap_or_default();
+            let fields = match stat.rfind(')') {
+                Some(end) => stat[end + 1..].split_whitespace().collect::<Vec<_>>(),
+                None => return false,
+            };
+            matches!(fields[..], [state, _ppid, group, ..] if state != "Z" && group == pgid.to_string())
+        })
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn it_captures_stdout_and_stderr() {
+        use regex::Regex;
+
+        let process = ManagedProcess::spawn(sh("echo out; echo err >&2; sleep 10"), None).unwrap();
+
+        let ready = ready_when_or_timeout(
+            process
+                .logs()
+                .probe(Regex::new("out\n(.|\n)*err|err\n(.|\n)*out").unwrap()),
+            Backoff::fixed(Duration::from_millis(10)),
+            Duration::from_secs(1),
+        );
+
+        assert!(ready.is_ok(), "{:?}", ready);
+        process.stop(Duration::from_secs(1)).unwrap();
+    }
+
+    #[test]
+    fn it_kills_the_group_after_the_grace_period() {
+        let stopwatch = Stopwatch::start_new();
+        // The shell and its sleep both ignore SIGTERM
+        let process =
+            ManagedProcess::spawn(sh("trap '' TERM; echo ready; sleep 10 & wait"), None).unwrap();
+        let pid = process.pid();
+        ready_when_or_timeout(
+            logs_contain(&process.logs(), "ready"),
+            Backoff::fixed(Duration::from_millis(10)),
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+        process.stop(Duration::from_millis(100)).unwrap();
+
+        assert!(stopwatch.elapsed_ms() < 1000);
+        assert!(!group_is_alive(pid));
+    }
+
+    #[test]
+    fn it_does_not_wait_for_a_process_that_left_the_group() {
+        let stopwatch = Stopwatch::start_new();
+        // Keeps the output open after the group is killed
+        let process = ManagedProcess::spawn(sh("setsid sleep 5 & echo ready; wait"), None).unwrap();
+        ready_when_or_timeout(
+            logs_contain(&process.logs(), "ready"),
+            Backoff::fixed(Duration::from_millis(10)),
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+        process.stop(Duration::from_millis(100)).unwrap();
+
+        assert!(stopwatch.elapsed_ms() < 3000);
+    }
+
+    #[test]
+    fn it_kills_the_group_on_drop() {
+        let process = ManagedProcess::spawn(sh("sleep 10 & wait"), None).unwrap();
+        let pid = process.pid();
+
+        drop(process);
+
+        assert!(!group_is_alive(pid));
+    }
+
+    struct Echo;
+    impl ProcessSpec for Echo {
+        fn command() -> Command {
+            sh("echo listening; sleep 10")
+        }
+
+        fn ready(logs: &ProcessLogs) -> Option<ProbeFn> {
+            Some(logs_contain(logs, "listening"))
+        }
+    }
+
+    #[test]
+    fn it_launches_the_process_until_teardown() {
+        let mut shared_context = SharedContext::default();
+        let context = ProcessContext::<Echo>::setup(&mut shared_context);
+
+        let handle = shared_context.get::<ProcessHandle<Echo>>().unwrap();
+        assert_eq!(handle.logs.contents(), "listening\n");
+        assert!(group_is_alive(handle.pid));
+
+        shared_context.register(TestOutcome::Failed("boom".to_owned()));
+        context.teardown(&mut shared_context);
+
+        assert!(!group_is_alive(handle.pid));
+    }
+}
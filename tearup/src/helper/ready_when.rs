@@ -1,4 +1,11 @@
-use std::{thread::sleep, time::Duration};
+use std::{
+    collections::hash_map::RandomState,
+    fmt::{self, Display},
+    hash::{BuildHasher, Hasher},
+    thread::sleep,
+    time::Duration,
+};
+use stopwatch::Stopwatch;
 
 #[cfg(feature = "async")]
 pub use asyncc::*;
@@ -14,9 +21,119 @@ pub fn ready_when(predicate: PredicateFn, retry_interval: Duration) {
     }
 }
 
+/// Try the predicate until it succeeds, waiting between attempts according to the backoff.
+/// Gives up once `timeout` is elapsed, with the last error of the predicate.
+pub fn ready_when_or_timeout<E>(
+    mut predicate: impl FnMut() -> Result<(), E>,
+    backoff: Backoff,
+    timeout: Duration,
+) -> Result<(), ReadyWhenError<E>> {
+    let stopwatch = Stopwatch::start_new();
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        let last_error = match predicate() {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+
+        match backoff.next_wait(attempts, stopwatch.elapsed(), timeout) {
+            Some(wait) => sleep(wait),
+            None => {
+                return Err(ReadyWhenError {
+                    attempts,
+                    elapsed: stopwatch.elapsed(),
+                    last_error,
+                })
+            }
+        }
+    }
+}
+
+/// How long to wait between two attempts of a predicate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backoff {
+    initial: Duration,
+    factor: u32,
+    max: Duration,
+    jitter: bool,
+}
+
+impl Backoff {
+    /// Always wait `interval`.
+    pub fn fixed(interval: Duration) -> Self {
+        Self {
+            initial: interval,
+            factor: 1,
+            max: interval,
+            jitter: false,
+        }
+    }
+
+    /// Wait `initial` then double the wait after each attempt, up to `max`.
+    pub fn exponential(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            factor: 2,
+            max,
+            jitter: false,
+        }
+    }
+
+    /// Randomly shorten each wait by up to a half so retries from several tests spread out.
+    pub fn with_jitter(mut self) -> Self {
+        self.jitter = true;
+        self
+    }
+
+    /// The wait after the failed attempt number `attempt`, starting at 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .factor
+            .checked_pow(attempt.saturating_sub(1))
+            .and_then(|factor| self.initial.checked_mul(factor))
+            .map_or(self.max, |delay| delay.min(self.max));
+
+        if self.jitter {
+            let random = RandomState::new().build_hasher().finish();
+            delay / 2 + (delay / 2).mul_f64(random as f64 / u64::MAX as f64)
+        } else {
+            delay
+        }
+    }
+
+    fn next_wait(&self, attempt: u32, elapsed: Duration, timeout: Duration) -> Option<Duration> {
+        if elapsed >= timeout {
+            return None;
+        }
+        Some(self.delay(attempt).min(timeout - elapsed))
+    }
+}
+
+/// The predicate of `ready_when_or_timeout` never succeeded.
+#[derive(Debug, PartialEq)]
+pub struct ReadyWhenError<E> {
+    pub attempts: u32,
+    pub elapsed: Duration,
+    pub last_error: E,
+}
+
+impl<E: Display> Display for ReadyWhenError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "not ready after {} attempts in {:?}, last error: {}",
+            self.attempts, self.elapsed, self.last_error
+        )
+    }
+}
+
+impl<E: Display + fmt::Debug> std::error::Error for ReadyWhenError<E> {}
+
 #[cfg(test)]
 mod test {
-    use super::ready_when;
+    use super::{ready_when, ready_when_or_timeout, Backoff};
     use std::time::Duration;
     use stopwatch::Stopwatch;
 
@@ -31,6 +148,67 @@ mod test {
         assert_around_100ms_(&stopwatch);
     }
 
+    #[test]
+    fn it_wait_until_predicate_succeeds() {
+        let mut attempts = 0;
+        let ready = ready_when_or_timeout(
+            || {
+                attempts += 1;
+                if attempts < 3 {
+                    Err("connection refused")
+                } else {
+                    Ok(())
+                }
+            },
+            Backoff::fixed(Duration::from_millis(10)),
+            Duration::from_millis(100),
+        );
+
+        assert_eq!(ready, Ok(()));
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn it_timeouts_with_the_last_error() {
+        let stopwatch = Stopwatch::start_new();
+        let mut attempts = 0;
+
+        let err = ready_when_or_timeout(
+            || {
+                attempts += 1;
+                Err(format!("HTTP 503 #{}", attempts))
+            },
+            Backoff::fixed(Duration::from_millis(30)),
+            Duration::from_millis(100),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.attempts, 5);
+        assert_eq!(err.last_error, "HTTP 503 #5");
+        assert!(err.elapsed >= Duration::from_millis(100));
+        assert_around_100ms_(&stopwatch);
+    }
+
+    #[test]
+    fn it_backs_off_exponentially() {
+        let backoff = Backoff::exponential(Duration::from_millis(10), Duration::from_millis(50));
+
+        let delays = (1..=5).map(|attempt| backoff.delay(attempt).as_millis());
+        assert_eq!(delays.collect::<Vec<_>>(), vec![10, 20, 40, 50, 50]);
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn it_shortens_delays_with_jitter() {
+        let backoff = Backoff::fixed(Duration::from_millis(100)).with_jitter();
+
+        for attempt in 1..20 {
+            let delay = backoff.delay(attempt);
+            assert!(delay >= Duration::from_millis(50), "{:?} < 50ms", delay);
+            assert!(delay <= Duration::from_millis(100), "{:?} > 100ms", delay);
+        }
+    }
+
     fn assert_around_100ms_(stopwatch: &Stopwatch) {
         let ms = stopwatch.elapsed_ms();
         assert!(115 > ms, "stopwatch has {} elapsed ms > 115", ms);
@@ -41,8 +219,11 @@ mod test {
 #[cfg(feature = "async")]
 mod asyncc {
     use futures::future::BoxFuture;
-    use std::time::Duration;
-    use tokio::time::sleep;
+    use std::{future::Future, time::Duration};
+    use stopwatch::Stopwatch;
+
+    use super::{Backoff, ReadyWhenError};
+    use crate::runtime::sleep;
 
     /// Periadically try the predicate waiting for the given duration.
     ///
@@ -58,9 +239,43 @@ mod asyncc {
         }
     }
 
+    /// Same as `ready_when_or_timeout` with an async predicate.
+    pub async fn async_ready_when_or_timeout<PredicateFn, PredicateFuture, E>(
+        mut predicate: PredicateFn,
+        backoff: Backoff,
+        timeout: Duration,
+    ) -> Result<(), ReadyWhenError<E>>
+    where
+        PredicateFn: FnMut() -> PredicateFuture + Send,
+        PredicateFuture: Future<Output = Result<(), E>> + Send,
+    {
+        let stopwatch = Stopwatch::start_new();
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+            let last_error = match predicate().await {
+                Ok(()) => return Ok(()),
+                Err(err) => err,
+            };
+
+            match backoff.next_wait(attempts, stopwatch.elapsed(), timeout) {
+                Some(wait) => sleep(wait).await,
+                None => {
+                    return Err(ReadyWhenError {
+                        attempts,
+                        elapsed: stopwatch.elapsed(),
+                        last_error,
+                    })
+                }
+            }
+        }
+    }
+
     #[cfg(test)]
     mod test {
-        use super::async_ready_when;
+        use super::{async_ready_when, async_ready_when_or_timeout};
+        use crate::helper::Backoff;
         use futures::FutureExt;
         use std::time::Duration;
         use stopwatch::Stopwatch;
@@ -77,6 +292,24 @@ mod asyncc {
             assert_around_100ms_(&stopwatch);
         }
 
+        #[tokio::test]
+        async fn it_timeouts_with_the_last_error() {
+            let stopwatch = Stopwatch::start_new();
+
+            let err = async_ready_when_or_timeout(
+                || async { Err::<(), _>("connection refused") },
+                Backoff::exponential(Duration::from_millis(10), Duration::from_millis(40)),
+                Duration::from_millis(100),
+            )
+            .await
+            .unwrap_err();
+
+            // 10 + 20 + 40 + 30 ms of waits
+            assert_eq!(err.attempts, 5);
+            assert_eq!(err.last_error, "connection refused");
+            assert_around_100ms_(&stopwatch);
+        }
+
         fn assert_around_100ms_(stopwatch: &Stopwatch) {
             let ms = stopwatch.elapsed_ms();
             assert!(115 > ms, "stopwatch has {} elapsed ms > 115", ms);
@@ -0,0 +1,201 @@
+use std::{
+    fs, io,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{Context, SharedContext, TestOutcome};
+
+#[cfg(feature = "async")]
+/// `TempDirContext` for async tests.
+pub type AsyncTempDirContext = crate::Async<TempDirContext>;
+
+/// The directory of a `TempDirContext`.
+#[derive(Clone, Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+/// Creates a unique directory per test and registers it as `TempDir`.
+/// It is removed at teardown, unless the test failed or `TEARUP_KEEP=1` is set: its path is printed instead.
+pub struct TempDirContext(PathBuf);
+
+impl Context for TempDirContext {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        let dir = create_unique_dir().expect("Failed to create the temporary directory");
+        shared_context.register(TempDir(dir.clone()));
+        Self(dir)
+    }
+
+    fn teardown(self, shared_context: &mut SharedContext) {
+        clean(&self.0, shared_context);
+    }
+}
+
+/// The tree copied in a `TemplateDirContext`.
+pub trait TempDirTemplate: Send + Sync + 'static {
+    /// Relative to the crate root, e.g. `tests/fixtures/project`.
+    fn template() -> PathBuf;
+}
+
+/// Same as `TempDirContext` with the template copied in, use it through an alias:
+///
+/// ```ignore
+/// struct Project;
+/// impl TempDirTemplate for Project {
+///     fn template() -> PathBuf {
+///         "tests/fixtures/project".into()
+///     }
+/// }
+///
+/// type ProjectDirContext = TemplateDirContext<Project>;
+/// // or for async tests
+/// type AsyncProjectDirContext = Async<TemplateDirContext<Project>>;
+/// ```
+pub struct TemplateDirContext<T: TempDirTemplate> {
+    dir: PathBuf,
+    _template: PhantomData<T>,
+}
+
+impl<T: TempDirTemplate> Context for TemplateDirContext<T> {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        let template = super::crate_root().join(T::template());
+        let dir = create_unique_dir().expect("Failed to create the temporary directory");
+        if let Err(err) = copy_tree(&template, &dir) {
+            let _ = fs::remove_dir_all(&dir);
+            panic!("Failed to copy {}: {}", template.display(), err);
+        }
+
+        shared_context.register(TempDir(dir.clone()));
+        Self {
+            dir,
+            _template: PhantomData,
+        }
+    }
+
+    fn teardown(self, shared_context: &mut SharedContext) {
+        clean(&self.dir, shared_context);
+    }
+}
+
+fn create_unique_dir() -> io::Result<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    loop {
+        let dir = std::env::temp_dir().join(format!(
+            "tearup-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            // Left by a previous run with the same pid
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            fs::create_dir(&target)?;
+            copy_tree(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+fn clean(dir: &Path, shared_context: &mut SharedContext) {
+    let outcome = shared_context.get::<TestOutcome>();
+    let keep_requested = std::env::var("TEARUP_KEEP").ok();
+
+    if should_keep(outcome.as_ref(), keep_requested.as_deref()) {
+        eprintln!("Kept temporary directory {}", dir.display());
+    } else if let Err(err) = fs::remove_dir_all(dir) {
+        eprintln!("Failed to remove {}: {}", dir.display(), err);
+    }
+}
+
+fn should_keep(outcome: Option<&TestOutcome>, keep_requested: Option<&str>) -> bool {
+    keep_requested == Some("1") || matches!(outcome, Some(TestOutcome::Failed(_)))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::PathBuf};
+
+    use super::{should_keep, TempDir, TempDirContext, TempDirTemplate, TemplateDirContext};
+    use crate::{Context, SharedContext, TestOutcome};
+
+    #[test]
+    fn it_creates_a_unique_directory_removed_at_teardown() {
+        let mut first_shared_context = SharedContext::default();
+        let first = TempDirContext::setup(&mut first_shared_context);
+        let mut second_shared_context = SharedContext::default();
+        let second = TempDirContext::setup(&mut second_shared_context);
+
+        let first_dir = first_shared_context.get::<TempDir>().unwrap();
+        let second_dir = second_shared_context.get::<TempDir>().unwrap();
+        assert_ne!(first_dir.path(), second_dir.path());
+        assert!(first_dir.path().is_dir());
+
+        first_shared_context.register(TestOutcome::Passed);
+        first.teardown(&mut first_shared_context);
+        second.teardown(&mut second_shared_context);
+
+        assert!(!first_dir.path().exists());
+    }
+
+    #[test]
+    fn it_keeps_the_directory_when_the_test_failed() {
+        let mut shared_context = SharedContext::default();
+        let context = TempDirContext::setup(&mut shared_context);
+        let dir = shared_context.get::<TempDir>().unwrap();
+
+        shared_context.register(TestOutcome::Failed("boom".to_owned()));
+        context.teardown(&mut shared_context);
+
+        assert!(dir.path().is_dir());
+        fs::remove_dir_all(dir.path()).unwrap();
+    }
+
+    #[test]
+    fn it_keeps_the_directory_on_demand() {
+        assert!(should_keep(Some(&TestOutcome::Passed), Some("1")));
+        assert!(should_keep(Some(&TestOutcome::Failed("".to_owned())), None));
+        assert!(!should_keep(Some(&TestOutcome::Passed), Some("0")));
+        assert!(!should_keep(None, None));
+    }
+
+    struct Project;
+    impl TempDirTemplate for Project {
+        fn template() -> PathBuf {
+            "src/helper".into()
+        }
+    }
+
+    #[test]
+    fn it_copies_the_template() {
+        let mut shared_context = SharedContext::default();
+        let context = TemplateDirContext::<Project>::setup(&mut shared_context);
+        let dir = shared_context.get::<TempDir>().unwrap();
+
+        let copy = fs::read_to_string(dir.path().join("temp_dir.rs")).unwrap();
+        assert_eq!(copy, include_str!("temp_dir.rs"));
+
+        context.teardown(&mut shared_context);
+        assert!(!dir.path().exists());
+    }
+}
@@ -1,8 +1,8 @@
 #[cfg(feature = "async")]
 pub use asyncc::*;
 use std::{
-    sync::{Arc, Mutex},
-    thread::sleep,
+    convert::Infallible,
+    sync::{Arc, Condvar, Mutex},
     time::Duration,
 };
 use stopwatch::Stopwatch;
@@ -11,122 +11,356 @@ use crate::TimeoutError;
 
 pub type ReadyFn = Box<dyn Fn() + Send + Sync>;
 
-pub struct TimeGate {
-    ready_flag: Arc<Mutex<bool>>,
-    ready_checks_interval: Duration,
+/// Wakes up a composite gate waiting on several gates.
+pub(crate) type Bell = Arc<(Mutex<()>, Condvar)>;
+
+struct Signal<T, E> {
+    slot: Mutex<Option<Result<T, E>>>,
+    condvar: Condvar,
+    watchers: Mutex<Vec<Bell>>,
+}
+
+/// Blocks until a notifier is called.
+/// The waiter is woken up as soon as the notifier is called,
+/// `ready_checks_interval` is only a polling fallback on top of it.
+///
+/// The signal can carry a payload `T`, like the address a server is bound to,
+/// or an error `E` so the waiter fails right away instead of timing out.
+pub struct TimeGate<T = (), E = Infallible> {
+    signal: Arc<Signal<T, E>>,
+    ready_checks_interval: Option<Duration>,
+}
+
+/// Why a gate has not been opened.
+#[derive(PartialEq, Debug)]
+pub enum GateError<E> {
+    Timeout(TimeoutError),
+    Failed(E),
+}
+
+impl<E> From<TimeoutError> for GateError<E> {
+    fn from(err: TimeoutError) -> Self {
+        GateError::Timeout(err)
+    }
 }
 
 impl TimeGate {
+    /// Woken up by the notifier, and checks the signal every `ready_checks_interval` on top of it.
     pub fn new(ready_checks_interval: Duration) -> Self {
+        Self::event_driven().with_ready_checks_interval(ready_checks_interval)
+    }
+
+    /// Only woken up by the notifier, without polling.
+    pub fn event_driven() -> Self {
+        Self::with_payload()
+    }
+}
+
+impl<T, E> TimeGate<T, E> {
+    /// A gate whose signal carries a `T` or an `E`, send them with a `sender()`.
+    pub fn with_payload() -> Self {
         TimeGate {
-            ready_flag: Arc::new(Mutex::new(false)),
-            ready_checks_interval,
+            signal: Arc::new(Signal {
+                slot: Mutex::new(None),
+                condvar: Condvar::new(),
+                watchers: Mutex::new(vec![]),
+            }),
+            ready_checks_interval: None,
         }
     }
 
-    pub fn notifier(&self) -> ReadyFn {
-        let ready_flag = self.ready_flag.clone();
+    /// Also check the signal every `ready_checks_interval`.
+    pub fn with_ready_checks_interval(mut self, ready_checks_interval: Duration) -> Self {
+        self.ready_checks_interval = Some(ready_checks_interval);
+        self
+    }
 
-        Box::new(move || {
-            let mut ready = ready_flag.lock().unwrap();
-            *ready = true;
-        })
+    pub fn sender(&self) -> GateSender<T, E> {
+        GateSender {
+            signal: self.signal.clone(),
+        }
     }
 
-    pub fn wait_signal(self) {
-        let ready = || *self.ready_flag.lock().unwrap();
+    pub fn wait_signal(self) -> Result<T, E> {
+        let condvar = &self.signal.condvar;
+        let mut slot = self.signal.slot.lock().unwrap();
+
+        loop {
+            if let Some(result) = slot.take() {
+                return result;
+            }
 
-        while !ready() {
-            sleep(self.ready_checks_interval);
+            slot = match self.ready_checks_interval {
+                Some(interval) => condvar.wait_timeout(slot, interval).unwrap().0,
+                None => condvar.wait(slot).unwrap(),
+            };
         }
     }
 
-    pub fn wait_signal_or_timeout(self, timeout: Duration) -> Result<(), TimeoutError> {
+    pub fn wait_signal_or_timeout(self, timeout: Duration) -> Result<T, GateError<E>> {
         let stopwatch = Stopwatch::start_new();
-        let ready = || *self.ready_flag.lock().unwrap();
+        let condvar = &self.signal.condvar;
+        let mut slot = self.signal.slot.lock().unwrap();
 
-        while !ready() {
-            if stopwatch.elapsed() >= timeout {
-                return Err(TimeoutError {
+        loop {
+            if let Some(result) = slot.take() {
+                return result.map_err(GateError::Failed);
+            }
+
+            let elapsed = stopwatch.elapsed();
+            if elapsed >= timeout {
+                return Err(GateError::Timeout(TimeoutError {
                     duration: timeout,
                     ready_checks_interval: self.ready_checks_interval,
-                });
+                }));
             }
-            sleep(self.ready_checks_interval);
+
+            let wait = match self.ready_checks_interval {
+                Some(interval) => interval.min(timeout - elapsed),
+                None => timeout - elapsed,
+            };
+            slot = condvar.wait_timeout(slot, wait).unwrap().0;
         }
+    }
 
-        Ok(())
+    pub(crate) fn take_signal(&self) -> Option<Result<T, E>> {
+        self.signal.slot.lock().unwrap().take()
+    }
+
+    /// Ring `bell` when the signal is sent.
+    pub(crate) fn watch(&self, bell: Bell) {
+        self.signal.watchers.lock().unwrap().push(bell);
+    }
+}
+
+impl<E: Send + 'static> TimeGate<(), E> {
+    pub fn notifier(&self) -> ReadyFn {
+        let sender = self.sender();
+
+        Box::new(move || sender.ready(()))
     }
 }
 
 impl Default for TimeGate {
     fn default() -> Self {
-        Self::new(Duration::from_millis(10))
+        Self::event_driven()
+    }
+}
+
+/// Opens a `TimeGate`, only the first signal is kept.
+pub struct GateSender<T, E> {
+    signal: Arc<Signal<T, E>>,
+}
+
+impl<T, E> GateSender<T, E> {
+    pub fn ready(&self, payload: T) {
+        self.send(Ok(payload));
+    }
+
+    pub fn fail(&self, error: E) {
+        self.send(Err(error));
+    }
+
+    fn send(&self, result: Result<T, E>) {
+        let mut slot = self.signal.slot.lock().unwrap();
+        if slot.is_some() {
+            return;
+        }
+        *slot = Some(result);
+        self.signal.condvar.notify_all();
+        // Released before ringing, the composite gate checks the slots while holding its bell
+        drop(slot);
+
+        for bell in self.signal.watchers.lock().unwrap().iter() {
+            let (lock, condvar) = &**bell;
+            let _guard = lock.lock().unwrap();
+            condvar.notify_all();
+        }
+    }
+}
+
+impl<T, E> Clone for GateSender<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            signal: self.signal.clone(),
+        }
     }
 }
 
 #[cfg(feature = "async")]
 mod asyncc {
 
-    use futures::future::BoxFuture;
+    use futures::future::{select, BoxFuture};
     pub use futures::future::FutureExt;
-    use std::{sync::Arc, time::Duration};
+    use std::{
+        convert::Infallible,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
     use stopwatch::Stopwatch;
-    use tokio::{sync::Mutex, time::sleep};
+    use tokio::sync::Notify;
 
-    use crate::TimeoutError;
+    use super::GateError;
+    use crate::{runtime::sleep, TimeoutError};
 
-    pub struct AsyncTimeGate {
-        ready_flag: Arc<Mutex<bool>>,
-        ready_checks_interval: Duration,
+    struct AsyncSignal<T, E> {
+        slot: Mutex<Option<Result<T, E>>>,
+        notify: Notify,
+        watchers: Mutex<Vec<Arc<Notify>>>,
     }
 
-    pub type AsyncReadyFn<'a> = Box<dyn Fn() -> BoxFuture<'a, ()> + Send + Sync>;
+    /// Same as `TimeGate` for async setups.
+    pub struct AsyncTimeGate<T = (), E = Infallible> {
+        signal: Arc<AsyncSignal<T, E>>,
+        ready_checks_interval: Option<Duration>,
+    }
+
+    pub type AsyncReadyFn = Box<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync>;
 
     impl AsyncTimeGate {
+        /// Woken up by the notifier, and checks the signal every `ready_checks_interval` on top of it.
         pub fn new(ready_checks_interval: Duration) -> Self {
+            Self::event_driven().with_ready_checks_interval(ready_checks_interval)
+        }
+
+        /// Only woken up by the notifier, without polling.
+        pub fn event_driven() -> Self {
+            Self::with_payload()
+        }
+    }
+
+    impl<T, E> AsyncTimeGate<T, E> {
+        /// A gate whose signal carries a `T` or an `E`, send them with a `sender()`.
+        pub fn with_payload() -> Self {
             AsyncTimeGate {
-                ready_flag: Arc::new(Mutex::new(false)),
-                ready_checks_interval,
+                signal: Arc::new(AsyncSignal {
+                    slot: Mutex::new(None),
+                    notify: Notify::new(),
+                    watchers: Mutex::new(vec![]),
+                }),
+                ready_checks_interval: None,
+            }
+        }
+
+        /// Also check the signal every `ready_checks_interval`.
+        pub fn with_ready_checks_interval(mut self, ready_checks_interval: Duration) -> Self {
+            self.ready_checks_interval = Some(ready_checks_interval);
+            self
+        }
+
+        pub fn sender(&self) -> AsyncGateSender<T, E> {
+            AsyncGateSender {
+                signal: self.signal.clone(),
             }
         }
 
-        pub fn notifier<'a>(&self) -> AsyncReadyFn<'a> {
-            let ready_flag = self.ready_flag.clone();
+        pub async fn wait_signal(self) -> Result<T, E> {
+            loop {
+                if let Some(result) = self.take_signal() {
+                    return result;
+                }
 
-            Box::new(move || {
-                let ready_flag = ready_flag.clone();
-                Box::pin(async move {
-                    let mut ready_flag = ready_flag.lock().await;
-                    *ready_flag = true;
-                })
-            })
+                self.wait_notification(self.ready_checks_interval).await;
+            }
         }
 
-        pub async fn wait_signal_or_timeout(self, timeout: Duration) -> Result<(), TimeoutError> {
+        pub async fn wait_signal_or_timeout(self, timeout: Duration) -> Result<T, GateError<E>> {
             let stopwatch = Stopwatch::start_new();
 
-            while !self.is_ready().await {
-                if stopwatch.elapsed() >= timeout {
-                    return Err(TimeoutError {
+            loop {
+                if let Some(result) = self.take_signal() {
+                    return result.map_err(GateError::Failed);
+                }
+
+                let elapsed = stopwatch.elapsed();
+                if elapsed >= timeout {
+                    return Err(GateError::Timeout(TimeoutError {
                         duration: timeout,
                         ready_checks_interval: self.ready_checks_interval,
-                    });
+                    }));
                 }
-                sleep(self.ready_checks_interval).await;
+
+                let wait = match self.ready_checks_interval {
+                    Some(interval) => interval.min(timeout - elapsed),
+                    None => timeout - elapsed,
+                };
+                self.wait_notification(Some(wait)).await;
             }
+        }
+
+        pub(crate) fn take_signal(&self) -> Option<Result<T, E>> {
+            self.signal.slot.lock().unwrap().take()
+        }
 
-            Ok(())
+        /// Notify `bell` when the signal is sent.
+        pub(crate) fn watch(&self, bell: Arc<Notify>) {
+            self.signal.watchers.lock().unwrap().push(bell);
         }
 
-        async fn is_ready(&self) -> bool {
-            *self.ready_flag.lock().await
+        async fn wait_notification(&self, max_wait: Option<Duration>) {
+            let notified = self.signal.notify.notified();
+
+            match max_wait {
+                Some(max_wait) => {
+                    select(Box::pin(notified), Box::pin(sleep(max_wait))).await;
+                }
+                None => notified.await,
+            }
+        }
+    }
+
+    impl<E: Send + 'static> AsyncTimeGate<(), E> {
+        pub fn notifier(&self) -> AsyncReadyFn {
+            let sender = self.sender();
+
+            Box::new(move || {
+                sender.ready(());
+                Box::pin(async {})
+            })
         }
     }
 
     impl Default for AsyncTimeGate {
         fn default() -> Self {
-            Self::new(Duration::from_millis(10))
+            Self::event_driven()
+        }
+    }
+
+    /// Opens an `AsyncTimeGate`, only the first signal is kept.
+    pub struct AsyncGateSender<T, E> {
+        signal: Arc<AsyncSignal<T, E>>,
+    }
+
+    impl<T, E> AsyncGateSender<T, E> {
+        pub fn ready(&self, payload: T) {
+            self.send(Ok(payload));
+        }
+
+        pub fn fail(&self, error: E) {
+            self.send(Err(error));
+        }
+
+        fn send(&self, result: Result<T, E>) {
+            let mut slot = self.signal.slot.lock().unwrap();
+            if slot.is_some() {
+                return;
+            }
+            *slot = Some(result);
+            drop(slot);
+
+            // Keeps a permit if the waiter is not waiting yet
+            self.signal.notify.notify_one();
+            for bell in self.signal.watchers.lock().unwrap().iter() {
+                bell.notify_one();
+            }
+        }
+    }
+
+    impl<T, E> Clone for AsyncGateSender<T, E> {
+        fn clone(&self) -> Self {
+            Self {
+                signal: self.signal.clone(),
+            }
         }
     }
 
@@ -136,7 +370,7 @@ mod asyncc {
         use stopwatch::Stopwatch;
         use tokio::{spawn, time::sleep};
 
-        use crate::TimeoutError;
+        use crate::{helper::GateError, TimeoutError};
 
         use super::AsyncTimeGate;
 
@@ -150,13 +384,9 @@ mod asyncc {
             spawn(async move {
                 sleep(Duration::from_millis(100)).await;
                 ready().await;
-            })
-            .await
-            .unwrap();
+            });
 
-            gate.wait_signal_or_timeout(Duration::from_millis(115))
-                .await
-                .unwrap();
+            gate.wait_signal().await.unwrap();
             assert_around_100ms_(&stopwatch);
         }
 
@@ -179,6 +409,54 @@ mod asyncc {
             assert_around_100ms_(&stopwatch);
         }
 
+        #[tokio::test]
+        async fn it_waits_a_signal_sent_before_waiting() {
+            let gate = AsyncTimeGate::default();
+            gate.notifier()().await;
+
+            assert!(gate
+                .wait_signal_or_timeout(Duration::from_millis(10))
+                .await
+                .is_ok());
+        }
+
+        #[tokio::test]
+        async fn it_gives_the_payload() {
+            let gate = AsyncTimeGate::<u16, String>::with_payload();
+            let sender = gate.sender();
+
+            spawn(async move {
+                sleep(Duration::from_millis(10)).await;
+                sender.ready(8080);
+            });
+
+            assert_eq!(
+                gate.wait_signal_or_timeout(Duration::from_millis(100))
+                    .await,
+                Ok(8080)
+            );
+        }
+
+        #[tokio::test]
+        async fn it_fails_without_waiting_the_timeout() {
+            let stopwatch = Stopwatch::start_new();
+
+            let gate = AsyncTimeGate::<u16, String>::with_payload();
+            let sender = gate.sender();
+
+            spawn(async move {
+                sleep(Duration::from_millis(10)).await;
+                sender.fail("failed to bind".to_owned());
+            });
+
+            assert_eq!(
+                gate.wait_signal_or_timeout(Duration::from_millis(1000))
+                    .await,
+                Err(GateError::Failed("failed to bind".to_owned()))
+            );
+            assert!(stopwatch.elapsed_ms() < 100);
+        }
+
         #[tokio::test]
         async fn it_timeouts() {
             let stopwatch = Stopwatch::start_new();
@@ -194,12 +472,27 @@ mod asyncc {
             let timeout = Duration::from_millis(85);
             assert_eq!(
                 gate.wait_signal_or_timeout(Duration::from_millis(85)).await,
-                Err(TimeoutError {
+                Err(GateError::Timeout(TimeoutError {
                     duration: timeout,
-                    ready_checks_interval: Duration::from_millis(10),
-                })
+                    ready_checks_interval: None,
+                }))
+            );
+            let ms = stopwatch.elapsed_ms();
+            assert!(ms >= 85, "stopwatch has {} elapsed ms < 85", ms);
+            assert!(115 > ms, "stopwatch has {} elapsed ms > 115", ms);
+        }
+
+        #[tokio::test]
+        async fn it_timeouts_with_polling_fallback() {
+            let gate = AsyncTimeGate::new(Duration::from_millis(10));
+
+            assert_eq!(
+                gate.wait_signal_or_timeout(Duration::from_millis(20)).await,
+                Err(GateError::Timeout(TimeoutError {
+                    duration: Duration::from_millis(20),
+                    ready_checks_interval: Some(Duration::from_millis(10)),
+                }))
             );
-            assert_around_100ms_(&stopwatch);
         }
 
         fn assert_around_100ms_(stopwatch: &Stopwatch) {
@@ -218,7 +511,7 @@ mod test {
     };
     use stopwatch::Stopwatch;
 
-    use super::TimeGate;
+    use super::{GateError, TimeGate};
     use crate::TimeoutError;
 
     #[test]
@@ -233,7 +526,7 @@ mod test {
             ready();
         });
 
-        gate.wait_signal();
+        gate.wait_signal().unwrap();
         assert_around_100ms_(&stopwatch);
     }
 
@@ -255,6 +548,68 @@ mod test {
         assert_around_100ms_(&stopwatch);
     }
 
+    #[test]
+    fn it_wakes_up_as_soon_as_notified() {
+        let gate = TimeGate::default();
+        let ready = gate.notifier();
+        let notified_at = spawn(move || {
+            sleep(Duration::from_millis(20));
+            ready();
+            Stopwatch::start_new()
+        });
+
+        gate.wait_signal().unwrap();
+
+        let latency = notified_at.join().unwrap().elapsed_ms();
+        assert!(latency < 5, "woke up {} ms after the signal", latency);
+    }
+
+    #[test]
+    fn it_gives_the_payload() {
+        let gate = TimeGate::<u16, String>::with_payload();
+        let sender = gate.sender();
+
+        spawn(move || {
+            sleep(Duration::from_millis(10));
+            sender.ready(8080);
+        });
+
+        assert_eq!(
+            gate.wait_signal_or_timeout(Duration::from_millis(100)),
+            Ok(8080)
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_first_signal() {
+        let gate = TimeGate::<u16, String>::with_payload();
+        let sender = gate.sender();
+
+        sender.fail("failed to bind".to_owned());
+        sender.ready(8080);
+
+        assert_eq!(gate.wait_signal(), Err("failed to bind".to_owned()));
+    }
+
+    #[test]
+    fn it_fails_without_waiting_the_timeout() {
+        let stopwatch = Stopwatch::start_new();
+
+        let gate = TimeGate::<u16, String>::with_payload();
+        let sender = gate.sender();
+
+        spawn(move || {
+            sleep(Duration::from_millis(10));
+            sender.fail("failed to bind".to_owned());
+        });
+
+        assert_eq!(
+            gate.wait_signal_or_timeout(Duration::from_millis(1000)),
+            Err(GateError::Failed("failed to bind".to_owned()))
+        );
+        assert!(stopwatch.elapsed_ms() < 100);
+    }
+
     #[test]
     fn it_timeouts() {
         let stopwatch = Stopwatch::start_new();
@@ -270,12 +625,27 @@ mod test {
         let timeout = Duration::from_millis(85);
         assert_eq!(
             gate.wait_signal_or_timeout(Duration::from_millis(85)),
-            Err(TimeoutError {
+            Err(GateError::Timeout(TimeoutError {
                 duration: timeout,
-                ready_checks_interval: Duration::from_millis(10),
-            })
+                ready_checks_interval: None,
+            }))
+        );
+        let ms = stopwatch.elapsed_ms();
+        assert!(ms >= 85, "stopwatch has {} elapsed ms < 85", ms);
+        assert!(115 > ms, "stopwatch has {} elapsed ms > 115", ms);
+    }
+
+    #[test]
+    fn it_timeouts_with_polling_fallback() {
+        let gate = TimeGate::new(Duration::from_millis(10));
+
+        assert_eq!(
+            gate.wait_signal_or_timeout(Duration::from_millis(20)),
+            Err(GateError::Timeout(TimeoutError {
+                duration: Duration::from_millis(20),
+                ready_checks_interval: Some(Duration::from_millis(10)),
+            }))
         );
-        assert_around_100ms_(&stopwatch);
     }
 
     fn assert_around_100ms_(stopwatch: &Stopwatch) {
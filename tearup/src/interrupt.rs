@@ -0,0 +1,506 @@
+#![cfg_attr(not(unix), allow(dead_code))]
+
+use std::{
+    any::Any,
+    cell::Cell,
+    fmt::Write as _,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Mutex, MutexGuard, Once,
+    },
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "async")]
+use crate::{runtime::BlockingRuntime, AsyncContext, AsyncSharedContext};
+use crate::{Context, SharedContext, TestOutcome};
+
+/// The contexts between their setup and the end of their teardown.
+static LIVE: Mutex<Vec<Live>> = Mutex::new(vec![]);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static HANDLE_SIGNALS: Once = Once::new();
+
+/// Overrides the time given to the teardowns after SIGINT or SIGTERM, in seconds.
+const GRACE_PERIOD: &str = "TEARUP_GRACE_PERIOD";
+const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+struct Live {
+    id: u64,
+    test: &'static str,
+    context: &'static str,
+    fallback_teardown: fn(&TestOutcome) -> bool,
+    /// Taken back for the teardown by the test or by the signal thread, whichever comes first.
+    held: Option<Box<dyn Held>>,
+}
+
+/// A context instance handed over during the test, with what its teardown needs.
+trait Held: Send {
+    fn tear_down(self: Box<Self>, outcome: &TestOutcome);
+
+    fn into_context(self: Box<Self>) -> Box<dyn Any>;
+}
+
+struct Instance<C, S> {
+    context: C,
+    shared_context: S,
+    tear_down: fn(C, S, &TestOutcome),
+}
+
+impl<C: Send + 'static, S: Send> Held for Instance<C, S> {
+    fn tear_down(self: Box<Self>, outcome: &TestOutcome) {
+        (self.tear_down)(self.context, self.shared_context, outcome)
+    }
+
+    fn into_context(self: Box<Self>) -> Box<dyn Any> {
+        Box::new(self.context)
+    }
+}
+
+/// A test whose context is set up, held by the macros from before the setup to after the teardown.
+///
+/// On SIGINT or SIGTERM, e.g. Ctrl-C during `cargo test`, the contexts handed over with `LiveInstance`
+/// are torn down, the `fallback_teardown` of each type of the other live contexts runs once,
+/// all side by side within the grace period, 5s or `TEARUP_GRACE_PERIOD` seconds, then the process exits.
+/// The contexts left behind, one line per test, are listed in `target/tearup/interrupted-<pid>.txt`:
+/// those of the teardowns too slow or panicking, and of the fallbacks returning `false` like the default one.
+/// A second signal kills the process right away. Unix only.
+pub struct LiveContext {
+    id: u64,
+}
+
+impl LiveContext {
+    pub fn enter(
+        test: &'static str,
+        context: &'static str,
+        fallback_teardown: fn(&TestOutcome) -> bool,
+    ) -> Self {
+        HANDLE_SIGNALS.call_once(handle_signals);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        live().push(Live {
+            id,
+            test,
+            context,
+            fallback_teardown,
+            held: None,
+        });
+        Self { id }
+    }
+
+    fn hold<C: Send + 'static, S: Send + 'static>(
+        &self,
+        context: C,
+        shared_context: S,
+        tear_down: fn(C, S, &TestOutcome),
+    ) {
+        let instance = Box::new(Instance {
+            context,
+            shared_context,
+            tear_down,
+        });
+        if let Some(live) = live().iter_mut().find(|live| live.id == self.id) {
+            live.held = Some(instance);
+        }
+    }
+
+    /// `None` once the signal thread took the context to tear it down.
+    fn claim<C: 'static>(&self) -> Option<C> {
+        let held = live()
+            .iter_mut()
+            .find(|live| live.id == self.id)
+            .and_then(|live| live.held.take())?;
+        held.into_context().downcast().ok().map(|context| *context)
+    }
+}
+
+impl Drop for LiveContext {
+    fn drop(&mut self) {
+        let mut live = live();
+        let index = live.iter().position(|live| live.id == self.id);
+        let removed = index.map(|index| live.remove(index));
+        // The context of a test panicking before its teardown is dropped outside of the lock
+        drop(live);
+        drop(removed);
+    }
+}
+
+/// Hands a context over to its `LiveContext` for the test, to be torn down by the signal thread if need be.
+/// `(&&LiveInstance::new(context)).hold(&live, &shared_context)` picks `HoldInstance` for the `Send` contexts,
+/// `KeepInstance` otherwise: those stay with the test, only their `fallback_teardown` runs on SIGINT or SIGTERM.
+pub struct LiveInstance<C>(Cell<Option<C>>);
+
+impl<C> LiveInstance<C> {
+    pub fn new(context: C) -> Self {
+        Self(Cell::new(Some(context)))
+    }
+
+    fn take(&self) -> C {
+        self.0.take().expect("A live instance is held once")
+    }
+}
+
+/// The context given back by `HeldContext::claim` for the teardown.
+pub struct HeldContext<C>(Option<C>);
+
+impl<C: 'static> HeldContext<C> {
+    /// `None` once the signal thread took the context to tear it down.
+    pub fn claim(self, live: &LiveContext) -> Option<C> {
+        self.0.or_else(|| live.claim())
+    }
+}
+
+pub trait HoldInstance<C> {
+    fn hold(&self, live: &LiveContext, shared_context: &SharedContext) -> HeldContext<C>;
+}
+
+impl<C: Context + Send + 'static> HoldInstance<C> for &LiveInstance<C> {
+    fn hold(&self, live: &LiveContext, shared_context: &SharedContext) -> HeldContext<C> {
+        live.hold(self.take(), shared_context.clone(), tear_down::<C>);
+        HeldContext(None)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait HoldAsyncInstance<C> {
+    fn hold_async(&self, live: &LiveContext, shared_context: &AsyncSharedContext)
+        -> HeldContext<C>;
+}
+
+#[cfg(feature = "async")]
+impl<C: AsyncContext + 'static> HoldAsyncInstance<C> for &LiveInstance<C> {
+    fn hold_async(
+        &self,
+        live: &LiveContext,
+        shared_context: &AsyncSharedContext,
+    ) -> HeldContext<C> {
+        live.hold(self.take(), shared_context.clone(), tear_down_async::<C>);
+        HeldContext(None)
+    }
+}
+
+pub trait KeepInstance<C> {
+    fn hold(&self, _live: &LiveContext, _shared_context: &SharedContext) -> HeldContext<C>;
+
+    #[cfg(feature = "async")]
+    fn hold_async(
+        &self,
+        _live: &LiveContext,
+        _shared_context: &AsyncSharedContext,
+    ) -> HeldContext<C>;
+}
+
+impl<C> KeepInstance<C> for LiveInstance<C> {
+    fn hold(&self, _live: &LiveContext, _shared_context: &SharedContext) -> HeldContext<C> {
+        HeldContext(Some(self.take()))
+    }
+
+    #[cfg(feature = "async")]
+    fn hold_async(
+        &self,
+        _live: &LiveContext,
+        _shared_context: &AsyncSharedContext,
+    ) -> HeldContext<C> {
+        HeldContext(Some(self.take()))
+    }
+}
+
+/// On the signal thread, the shared context as it was when the context was handed over.
+fn tear_down<C: Context>(context: C, mut shared_context: SharedContext, outcome: &TestOutcome) {
+    shared_context.register(outcome.clone());
+    context.launch_teardown(&mut shared_context);
+}
+
+#[cfg(feature = "async")]
+fn tear_down_async<C: AsyncContext>(
+    context: C,
+    shared_context: AsyncSharedContext,
+    outcome: &TestOutcome,
+) {
+    BlockingRuntime::new().block_on(async {
+        shared_context.register(outcome.clone()).await;
+        context.launch_teardown(shared_context).await;
+    });
+}
+
+/// A test panicking while it registers must not stop the others from being torn down.
+fn live() -> MutexGuard<'static, Vec<Live>> {
+    LIVE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[derive(Debug, PartialEq)]
+struct Leak {
+    test: &'static str,
+    context: &'static str,
+    reason: String,
+}
+
+/// What the signal thread runs for the live contexts.
+enum Teardown {
+    Instance(Box<dyn Held>),
+    Fallback(fn(&TestOutcome) -> bool),
+}
+
+/// Tear down the contexts handed over and run the fallback teardown of each type of the other live contexts,
+/// every live context not cleaned up in time is a leak.
+fn tear_down_live(signal: &str, grace_period: Duration) -> Vec<Leak> {
+    // Claimed here, the tests no longer tear them down
+    let live: Vec<_> = live()
+        .iter_mut()
+        .map(|live| {
+            let held = live.held.take();
+            (live.test, live.context, live.fallback_teardown, held)
+        })
+        .collect();
+    if live.is_empty() {
+        return vec![];
+    }
+    eprintln!(
+        "Interrupted by {}, tearing down {} contexts within {:?}",
+        signal,
+        live.len(),
+        grace_period
+    );
+
+    // The fallback teardowns know the type only, once is enough for all its instances
+    let mut teardowns = vec![];
+    let mut fallbacks: Vec<(&'static str, usize)> = vec![];
+    let mut leaks = vec![];
+    for (test, context, fallback_teardown, held) in live {
+        let known = fallbacks.iter().find(|(ty, _)| *ty == context);
+        let index = match (held, known) {
+            (Some(held), _) => {
+                teardowns.push(Teardown::Instance(held));
+                teardowns.len() - 1
+            }
+            (None, Some((_, index))) => *index,
+            (None, None) => {
+                teardowns.push(Teardown::Fallback(fallback_teardown));
+                fallbacks.push((context, teardowns.len() - 1));
+                teardowns.len() - 1
+            }
+        };
+        leaks.push((test, context, index));
+    }
+
+    let (done, torn_down) = mpsc::channel();
+    let outcome = TestOutcome::Failed(format!("interrupted by {}", signal));
+    let mut reasons = vec![Some("not torn down within the grace period"); teardowns.len()];
+    // Side by side so a hanging one doesn't hold back the others
+    for (index, teardown) in teardowns.into_iter().enumerate() {
+        let (done, outcome) = (done.clone(), outcome.clone());
+        std::thread::spawn(move || {
+            let reason = match teardown {
+                Teardown::Instance(held) => {
+                    catch_unwind(AssertUnwindSafe(|| held.tear_down(&outcome)))
+                        .err()
+                        .map(|_| "teardown panicked")
+                }
+                Teardown::Fallback(fallback_teardown) => {
+                    match catch_unwind(AssertUnwindSafe(|| fallback_teardown(&outcome))) {
+                        Ok(true) => None,
+                        Ok(false) => Some("no fallback teardown"),
+                        Err(_) => Some("fallback teardown panicked"),
+                    }
+                }
+            };
+            let _ = done.send((index, reason));
+        });
+    }
+    // Disconnected once they are all done
+    drop(done);
+
+    let deadline = Instant::now() + grace_period;
+    while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+        match torn_down.recv_timeout(timeout) {
+            Ok((index, reason)) => reasons[index] = reason,
+            Err(_) => break,
+        }
+    }
+
+    leaks
+        .into_iter()
+        .filter_map(|(test, context, index)| {
+            Some(Leak {
+                test,
+                context,
+                reason: reasons[index]?.to_owned(),
+            })
+        })
+        .collect()
+}
+
+fn write_report(leaks: &[Leak]) -> std::io::Result<PathBuf> {
+    let dir = crate::helper::tearup_dir();
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("interrupted-{}.txt", std::process::id()));
+
+    let mut report = String::new();
+    for leak in leaks {
+        let _ = writeln!(report, "{}\t{}\t{}", leak.test, leak.context, leak.reason);
+    }
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+fn grace_period() -> Duration {
+    crate::helper::secs_var(GRACE_PERIOD).unwrap_or(DEFAULT_GRACE_PERIOD)
+}
+
+fn interrupted(signal: &str, code: i32) -> ! {
+    let leaks = tear_down_live(signal, grace_period());
+    if !leaks.is_empty() {
+        match write_report(&leaks) {
+            Ok(path) => eprintln!(
+                "{} contexts left behind, see {}",
+                leaks.len(),
+                path.display()
+            ),
+            Err(err) => eprintln!("{} contexts left behind: {}", leaks.len(), err),
+        }
+    }
+    std::process::exit(code)
+}
+
+#[cfg(unix)]
+fn handle_signals() {
+    use std::sync::atomic::{AtomicBool, AtomicI32};
+
+    static PIPE: AtomicI32 = AtomicI32::new(-1);
+    static RECEIVED: AtomicBool = AtomicBool::new(false);
+
+    // Only async-signal-safe calls: the teardowns run on the thread reading the pipe
+    extern "C" fn on_signal(signal: libc::c_int) {
+        if RECEIVED.swap(true, Ordering::SeqCst) {
+            unsafe {
+                libc::signal(signal, libc::SIG_DFL);
+                libc::raise(signal);
+            }
+            return;
+        }
+        let byte = signal as u8;
+        unsafe { libc::write(PIPE.load(Ordering::SeqCst), &byte as *const u8 as _, 1) };
+    }
+
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        eprintln!("The contexts won't be torn down on SIGINT or SIGTERM");
+        return;
+    }
+    let [read, write] = fds;
+    PIPE.store(write, Ordering::SeqCst);
+
+    std::thread::spawn(move || {
+        let mut byte = 0u8;
+        while unsafe { libc::read(read, &mut byte as *mut u8 as _, 1) } != 1 {}
+
+        let signal = byte as libc::c_int;
+        let name = match signal {
+            libc::SIGINT => "SIGINT",
+            _ => "SIGTERM",
+        };
+        interrupted(name, 128 + signal)
+    });
+
+    let on_signal: extern "C" fn(libc::c_int) = on_signal;
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        unsafe { libc::signal(signal, on_signal as libc::sighandler_t) };
+    }
+}
+
+#[cfg(not(unix))]
+fn handle_signals() {}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        rc::Rc,
+        sync::atomic::{AtomicUsize, Ordering},
+        thread::sleep,
+        time::Duration,
+    };
+
+    use super::{tear_down_live, HoldInstance, KeepInstance, Leak, LiveContext, LiveInstance};
+    use crate::{Context, SharedContext, TestOutcome};
+
+    static FALLBACKS: AtomicUsize = AtomicUsize::new(0);
+    static TEARDOWNS: AtomicUsize = AtomicUsize::new(0);
+
+    fn counted(outcome: &TestOutcome) -> bool {
+        assert_eq!(
+            outcome,
+            &TestOutcome::Failed("interrupted by SIGINT".to_owned())
+        );
+        FALLBACKS.fetch_add(1, Ordering::SeqCst);
+        true
+    }
+
+    fn hanging(_outcome: &TestOutcome) -> bool {
+        sleep(Duration::from_secs(1));
+        true
+    }
+
+    fn none(_outcome: &TestOutcome) -> bool {
+        false
+    }
+
+    struct Held;
+    impl Context for Held {
+        fn setup(_shared_context: &mut SharedContext) -> Self {
+            Self
+        }
+
+        fn teardown(self, shared_context: &mut SharedContext) {
+            assert_eq!(
+                shared_context.get::<TestOutcome>(),
+                Some(TestOutcome::Failed("interrupted by SIGINT".to_owned()))
+            );
+            TEARDOWNS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn it_tears_down_the_live_contexts() {
+        let claimed = LiveContext::enter("claimed", "interrupt::Held", none);
+        let held_context = (&&LiveInstance::new(Held)).hold(&claimed, &SharedContext::default());
+        assert!(held_context.claim(&claimed).is_some());
+        let kept = LiveInstance::new(Rc::new(Held)).hold(&claimed, &SharedContext::default());
+        assert!(kept.claim(&claimed).is_some());
+        drop(claimed);
+
+        let first = LiveContext::enter("first", "interrupt::Counted", counted);
+        let _second = LiveContext::enter("second", "interrupt::Counted", counted);
+        let _hanging = LiveContext::enter("third", "interrupt::Hanging", hanging);
+        let _fourth = LiveContext::enter("fourth", "interrupt::None", none);
+        let _fifth = LiveContext::enter("fifth", "interrupt::None", none);
+        let done = LiveContext::enter("done", "interrupt::Done", counted);
+        drop(done);
+        let held = LiveContext::enter("held", "interrupt::Held", none);
+        let held_context = (&&LiveInstance::new(Held)).hold(&held, &SharedContext::default());
+
+        let leaks = tear_down_live("SIGINT", Duration::from_millis(200));
+
+        assert_eq!(FALLBACKS.load(Ordering::SeqCst), 1);
+        assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 1);
+        assert!(held_context.claim(&held).is_none());
+        let leak = |test, context, reason: &str| Leak {
+            test,
+            context,
+            reason: reason.to_owned(),
+        };
+        assert_eq!(
+            leaks,
+            vec![
+                leak(
+                    "third",
+                    "interrupt::Hanging",
+                    "not torn down within the grace period"
+                ),
+                leak("fourth", "interrupt::None", "no fallback teardown"),
+                leak("fifth", "interrupt::None", "no fallback teardown"),
+            ]
+        );
+        drop(first);
+    }
+}
@@ -0,0 +1,220 @@
+//! What `#[tearup_test(MyContext, isolate = "process")]` expands to.
+//!
+//! The parent re-executes the test binary filtered to the single test, the child runs the setup, the test and the teardown.
+//! The output of the child is relayed, and if it dies before its teardown, e.g. on `std::process::exit` or a segfault,
+//! the parent runs the `fallback_teardown` of the context.
+//!
+//! The child prints how the test ended, the parent panics with the message of the child
+//! so `#[should_panic(expected = ..)]` applies to it, whatever the harness of the child made of it.
+use std::{
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    process::{Command, ExitStatus},
+};
+
+use crate::TestOutcome;
+
+/// Set in the child to the name of the test it runs.
+const ISOLATED_TEST: &str = "TEARUP_ISOLATED_TEST";
+/// Starts the line of the child telling how the test ended.
+const ENDED: &str = "tearup-isolate-ended: ";
+
+/// Whether this process is the child running the test.
+pub fn is_child(module_path: &str, name: &str) -> bool {
+    std::env::var(ISOLATED_TEST).ok() == Some(test_name(module_path, name))
+}
+
+/// In the child, run the test and tell the parent how it ended.
+pub fn run_as_child(test: impl FnOnce()) {
+    let execution = catch_unwind(AssertUnwindSafe(test));
+    report_end(execution);
+}
+
+/// In the child, run the async test and tell the parent how it ended.
+#[cfg(feature = "async")]
+pub async fn run_as_child_async(test: impl std::future::Future<Output = ()>) {
+    use futures::FutureExt;
+
+    let execution = AssertUnwindSafe(test).catch_unwind().await;
+    report_end(execution);
+}
+
+fn report_end(execution: std::thread::Result<()>) {
+    match TestOutcome::of(&execution) {
+        TestOutcome::Passed => eprintln!("{}passed", ENDED),
+        TestOutcome::Failed(message) => eprintln!(
+            "{}panicked {}",
+            ENDED,
+            message.replace('\\', "\\\\").replace('\n', "\\n")
+        ),
+    }
+
+    if let Err(panic) = execution {
+        resume_unwind(panic)
+    }
+}
+
+/// Run the test in a child process, panic if it failed.
+pub fn run_in_child(
+    module_path: &str,
+    name: &str,
+    fallback_teardown: impl FnOnce(&TestOutcome) -> bool,
+) {
+    let test = test_name(module_path, name);
+    let exe = std::env::current_exe().expect("Failed to find the test binary");
+
+    let output = Command::new(exe)
+        .args([test.as_str(), "--exact", "--nocapture", "--include-ignored"])
+        .env(ISOLATED_TEST, &test)
+        .output()
+        .unwrap_or_else(|err| panic!("Failed to launch {} in its process: {}", test, err));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for line in stdout.lines().filter(|line| !is_harness_line(line, &test)) {
+        println!("{}", line);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for line in stderr.lines().filter(|line| !line.starts_with(ENDED)) {
+        eprintln!("{}", line);
+    }
+
+    match Completion::of(&stdout, &stderr, output.status) {
+        Completion::Passed => {}
+        Completion::Failed(message) => panic!("{} failed in its process: {}", test, message),
+        Completion::NotFound => panic!(
+            "{} not found in the test binary, 'isolate' needs a test named after the function",
+            test
+        ),
+        Completion::Died(reason) => {
+            let reason = format!("{} died before its teardown: {}", test, reason);
+            if !fallback_teardown(&TestOutcome::Failed(reason.clone())) {
+                eprintln!("{} has no fallback teardown, nothing was cleaned up", test);
+            }
+            panic!("{}", reason)
+        }
+    }
+}
+
+/// The name given by the test harness: the module path without the crate.
+fn test_name(module_path: &str, name: &str) -> String {
+    match module_path.split_once("::") {
+        Some((_krate, path)) => format!("{}::{}", path, name),
+        None => name.to_owned(),
+    }
+}
+
+fn is_harness_line(line: &str, test: &str) -> bool {
+    line.is_empty()
+        || line == "running 1 test"
+        || line.starts_with("test result:")
+        || line.starts_with(&format!("test {} ...", test))
+}
+
+#[derive(Debug, PartialEq)]
+enum Completion {
+    Passed,
+    /// Panicked with the message, the teardown ran in the child.
+    Failed(String),
+    NotFound,
+    Died(String),
+}
+
+impl Completion {
+    fn of(stdout: &str, stderr: &str, status: ExitStatus) -> Self {
+        if stdout.contains("running 0 tests") {
+            return Completion::NotFound;
+        }
+
+        let ended = stderr.lines().find_map(|line| line.strip_prefix(ENDED));
+        match ended {
+            Some("passed") => Completion::Passed,
+            Some(ended) => {
+                Completion::Failed(unescape(ended.strip_prefix("panicked ").unwrap_or(ended)))
+            }
+            None => Completion::Died(describe(status)),
+        }
+    }
+}
+
+fn unescape(message: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = message.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some(escaped) => unescaped.push(escaped),
+            None => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(unix)]
+fn describe(status: ExitStatus) -> String {
+    use std::os::unix::process::ExitStatusExt;
+
+    match status.signal() {
+        Some(signal) => format!("killed by signal {}", signal),
+        None => status.to_string(),
+    }
+}
+
+#[cfg(not(unix))]
+fn describe(status: ExitStatus) -> String {
+    status.to_string()
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::{os::unix::process::ExitStatusExt, process::ExitStatus};
+
+    use super::{test_name, Completion};
+
+    #[test]
+    fn it_names_the_test_like_the_harness() {
+        assert_eq!(
+            test_name("impl_context::context::isolate", "it_runs"),
+            "context::isolate::it_runs"
+        );
+        assert_eq!(test_name("impl_context", "it_runs"), "it_runs");
+    }
+
+    #[test]
+    fn it_tells_how_the_child_completed() {
+        let running = "running 1 test\ntest it_runs ... ";
+        let exit = |code| ExitStatus::from_raw(code << 8);
+
+        assert_eq!(
+            Completion::of(running, "tearup-isolate-ended: passed\n", exit(0)),
+            Completion::Passed
+        );
+        assert_eq!(
+            Completion::of(
+                running,
+                "panicked at 'boom'\ntearup-isolate-ended: panicked boom\\nbam \\\\o/\n",
+                exit(101)
+            ),
+            Completion::Failed("boom\nbam \\o/".to_owned())
+        );
+        // With `#[should_panic]` the harness of the child is happy with a panic
+        assert_eq!(
+            Completion::of(running, "tearup-isolate-ended: panicked boom\n", exit(0)),
+            Completion::Failed("boom".to_owned())
+        );
+        assert_eq!(
+            Completion::of("running 0 tests\n\ntest result: ok.", "", exit(0)),
+            Completion::NotFound
+        );
+        assert_eq!(
+            Completion::of(running, "", exit(0)),
+            Completion::Died("exit status: 0".to_owned())
+        );
+        assert_eq!(
+            Completion::of(running, "", ExitStatus::from_raw(11)),
+            Completion::Died("killed by signal 11".to_owned())
+        );
+    }
+}
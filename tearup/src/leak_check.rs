@@ -0,0 +1,263 @@
+use std::collections::BTreeSet;
+
+/// The names, truncated to 15 bytes by Linux, of the pool threads of the runtimes,
+/// e.g. those started by the `spawn_blocking` of `Async<C>`.
+#[cfg(target_os = "linux")]
+const RUNTIME_THREADS: [&str; 5] = [
+    "tokio-runtime-w",
+    "async-std/runti",
+    "async-global-ex",
+    "async-io",
+    "blocking-",
+];
+
+/// Overrides the default leak check of the contexts: `off`, `warn` or `fail`.
+const LEAK_CHECK: &str = "TEARUP_LEAK_CHECK";
+
+/// What to do with the file descriptors, threads and child processes
+/// opened during a test and still there after its teardown, Linux only.
+///
+/// Set it for a context with `const LEAK_CHECK: Option<LeakCheck> = Some(LeakCheck::Fail);`,
+/// or for all of them with `TEARUP_LEAK_CHECK=warn`.
+///
+/// The resources are those of the whole process: the tests running at the same time show up too,
+/// run with `--test-threads=1`, `isolate = "process"` or in a `serial` group for exact results.
+/// The threads of the async runtimes' pools, idling for a while after use, are left out,
+/// the reactors of async-std and smol show up in the first test using them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LeakCheck {
+    Off,
+    /// Print the leaks.
+    Warn,
+    /// Fail a passing test with the leaks, print them for a failed one.
+    Fail,
+}
+
+impl LeakCheck {
+    /// `TEARUP_LEAK_CHECK`, off when not set.
+    pub fn global() -> Self {
+        match std::env::var(LEAK_CHECK).as_deref() {
+            Ok("warn") => LeakCheck::Warn,
+            Ok("fail") => LeakCheck::Fail,
+            Ok("off") | Err(_) => LeakCheck::Off,
+            Ok(other) => panic!("Expected {}=off|warn|fail, got {}", LEAK_CHECK, other),
+        }
+    }
+
+    /// For combinators: the strictest of the checks set by the contexts.
+    pub const fn strictest(a: Option<Self>, b: Option<Self>) -> Option<Self> {
+        match (a, b) {
+            (Some(a), Some(b)) if a as u8 >= b as u8 => Some(a),
+            (Some(_), Some(b)) => Some(b),
+            (Some(check), None) | (None, Some(check)) => Some(check),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Taken by the macros before the setup, checked after the teardown.
+pub struct LeakWatch {
+    context: &'static str,
+    check: LeakCheck,
+    before: Resources,
+}
+
+impl LeakWatch {
+    /// `None` follows `TEARUP_LEAK_CHECK`.
+    pub fn start(context: &'static str, check: Option<LeakCheck>) -> Self {
+        let check = check.unwrap_or_else(LeakCheck::global);
+        let before = match check {
+            LeakCheck::Off => Resources::default(),
+            _ => Resources::snapshot(),
+        };
+
+        Self {
+            context,
+            check,
+            before,
+        }
+    }
+
+    pub fn finish(self, test_failed: bool) {
+        if self.check == LeakCheck::Off {
+            return;
+        }
+
+        let after = Resources::snapshot();
+        let leaks = after.since(&self.before);
+        if leaks.is_empty() {
+            return;
+        }
+
+        let report = format!(
+            "{} leaked after its teardown:\n  {}",
+            self.context,
+            leaks.join("\n  ")
+        );
+        match (self.check, test_failed) {
+            (LeakCheck::Fail, false) => panic!("{}", report),
+            _ => eprintln!("{}", report),
+        }
+    }
+}
+
+/// The open file descriptors, the threads and the child processes, described.
+#[derive(Debug, Default)]
+struct Resources(BTreeSet<String>);
+
+impl Resources {
+    #[cfg(target_os = "linux")]
+    fn snapshot() -> Self {
+        let mut resources = BTreeSet::new();
+
+        for fd in entries("/proc/self/fd") {
+            if let Ok(target) = std::fs::read_link(format!("/proc/self/fd/{}", fd)) {
+                resources.insert(format!("fd {} -> {}", fd, target.display()));
+            }
+        }
+        for tid in entries("/proc/self/task") {
+            let name = std::fs::read_to_string(format!("/proc/self/task/{}/comm", tid));
+            let name = name.unwrap_or_default();
+            let name = name.trim();
+            if !RUNTIME_THREADS.iter().any(|pool| name.starts_with(pool)) {
+                resources.insert(format!("thread {} ({})", tid, name));
+            }
+        }
+        let pid = std::process::id().to_string();
+        for child in entries("/proc") {
+            // `pid (name) state ppid ..`, the name may contain spaces and parentheses
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", child)).unwrap_or_default();
+            let (name, fields) = match (stat.find('('), stat.rfind(')')) {
+                (Some(start), Some(end)) => (&stat[start + 1..end], &stat[end + 1..]),
+                _ => continue,
+            };
+            if fields.split_whitespace().nth(1) == Some(pid.as_str()) {
+                resources.insert(format!("child process {} ({})", child, name));
+            }
+        }
+
+        Self(resources)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn snapshot() -> Self {
+        Self::default()
+    }
+
+    fn since(&self, before: &Self) -> Vec<String> {
+        self.0.difference(&before.0).cloned().collect()
+    }
+}
+
+/// The numeric entries of a directory of `/proc`.
+#[cfg(target_os = "linux")]
+fn entries(dir: &str) -> Vec<u32> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .collect()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use std::{
+        process::Command,
+        sync::mpsc,
+        thread::{sleep, spawn},
+        time::Duration,
+    };
+
+    use super::{LeakCheck, LeakWatch, Resources};
+
+    /// Absolute, the working directory may be moved by another test.
+    const MANIFEST: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+
+    #[test]
+    fn it_finds_what_was_opened_since_the_snapshot() {
+        let before = Resources::snapshot();
+
+        let file = std::fs::File::open(MANIFEST).unwrap();
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = spawn(move || stopped.recv());
+        let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+        sleep(Duration::from_millis(50));
+
+        let leaks = Resources::snapshot().since(&before);
+        let leaked = |prefix: &str, suffix: &str| {
+            leaks
+                .iter()
+                .any(|leak| leak.starts_with(prefix) && leak.ends_with(suffix))
+        };
+        assert!(leaked("fd ", "tearup/Cargo.toml"), "{:?}", leaks);
+        assert!(leaked("thread ", ""), "{:?}", leaks);
+        assert!(
+            leaked(&format!("child process {} ", child.id()), "(sleep)"),
+            "{:?}",
+            leaks
+        );
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        stop.send(()).unwrap();
+        thread.join().unwrap().unwrap();
+        drop(file);
+    }
+
+    #[test]
+    fn it_leaves_out_the_threads_of_the_runtimes() {
+        let before = Resources::snapshot();
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let pool = std::thread::Builder::new()
+            .name("tokio-runtime-worker".to_owned())
+            .spawn(move || stopped.recv())
+            .unwrap();
+        sleep(Duration::from_millis(50));
+
+        let leaks = Resources::snapshot().since(&before);
+        assert!(
+            !leaks.iter().any(|leak| leak.contains("tokio-runtime")),
+            "{:?}",
+            leaks
+        );
+
+        stop.send(()).unwrap();
+        pool.join().unwrap().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Leaky leaked after its teardown:\n  fd ")]
+    fn it_fails_a_passing_test() {
+        let watch = LeakWatch::start("Leaky", Some(LeakCheck::Fail));
+        std::mem::forget(std::fs::File::open(MANIFEST).unwrap());
+        watch.finish(false);
+    }
+
+    #[test]
+    fn it_only_warns_for_a_failed_test() {
+        let watch = LeakWatch::start("Leaky", Some(LeakCheck::Fail));
+        std::mem::forget(std::fs::File::open(MANIFEST).unwrap());
+        watch.finish(true);
+    }
+
+    #[test]
+    fn it_keeps_the_strictest_check() {
+        let strictest = LeakCheck::strictest;
+        assert_eq!(
+            strictest(Some(LeakCheck::Warn), None),
+            Some(LeakCheck::Warn)
+        );
+        assert_eq!(
+            strictest(Some(LeakCheck::Fail), Some(LeakCheck::Warn)),
+            Some(LeakCheck::Fail)
+        );
+        assert_eq!(
+            strictest(Some(LeakCheck::Off), Some(LeakCheck::Warn)),
+            Some(LeakCheck::Warn)
+        );
+        assert_eq!(strictest(None, None), None);
+    }
+}
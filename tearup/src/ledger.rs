@@ -0,0 +1,355 @@
+//! External resources created by the contexts, e.g. databases, recorded on disk under `target/tearup/ledger`
+//! until their teardown so that `cargo tearup clean` can purge the ones left by killed test runs.
+//!
+//! ```ignore
+//! fn setup(shared_context: &mut SharedContext) -> Self {
+//!     let db_name = format!("go_{}", Uuid::new_v4().simple());
+//!     let recorded = Ledger::new()
+//!         .record(Resource::new("postgres-db", &db_name).cleanup(["dropdb", "--force", &db_name]))
+//!         .unwrap();
+//!     setup_db(&db_name);
+//!     Self { db_name, recorded }
+//! }
+//!
+//! fn teardown(self, _shared_context: &mut SharedContext) {
+//!     drop_db(&self.db_name);
+//!     self.recorded.remove();
+//! }
+//! ```
+use std::{
+    fmt::Write as _,
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Set for the cleanup command to the kind, id and data of the resource.
+const KIND: &str = "TEARUP_RESOURCE_KIND";
+const ID: &str = "TEARUP_RESOURCE_ID";
+const DATA: &str = "TEARUP_RESOURCE_DATA";
+
+/// Numbers the entries of the process, the kind and the id may not be unique once in a file name.
+static RECORDED: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory with one file per recorded resource.
+#[derive(Clone, Debug)]
+pub struct Ledger {
+    dir: PathBuf,
+}
+
+impl Ledger {
+    /// `target/tearup/ledger` of the running tests.
+    pub fn new() -> Self {
+        Self::at(crate::helper::tearup_dir().join("ledger"))
+    }
+
+    /// `tearup/ledger` in the temporary directory, used by the tests whose target directory isn't named `target`
+    /// and `CARGO_TARGET_DIR` isn't set.
+    pub fn fallback() -> Self {
+        Self::at(crate::helper::fallback_tearup_dir().join("ledger"))
+    }
+
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Record the resource before creating it, remove it once destroyed.
+    pub fn record(&self, resource: Resource) -> io::Result<Entry> {
+        std::fs::create_dir_all(&self.dir)?;
+        let pid = std::process::id();
+        let entry = Entry {
+            path: self.dir.join(format!(
+                "{}-{}-{}-{}.entry",
+                crate::serial::file_name(&resource.kind),
+                crate::serial::file_name(&resource.id),
+                pid,
+                RECORDED.fetch_add(1, Ordering::SeqCst)
+            )),
+            resource,
+            pid,
+        };
+        std::fs::write(&entry.path, entry.to_file())?;
+        Ok(entry)
+    }
+
+    /// Every recorded resource, the unreadable entries are skipped, e.g. removed while listing.
+    pub fn entries(&self) -> io::Result<Vec<Entry>> {
+        let dir = match std::fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+
+        let mut entries = vec![];
+        for path in dir.filter_map(|file| Some(file.ok()?.path())) {
+            if path.extension() != Some("entry".as_ref()) {
+                continue;
+            }
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            if let Some(entry) = Entry::from_file(path, &content) {
+                entries.push(entry);
+            }
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What to record about an external resource.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resource {
+    pub kind: String,
+    pub id: String,
+    /// Anything else the cleanup needs, e.g. a connection url.
+    pub data: String,
+    /// The program and arguments destroying the resource,
+    /// run with `TEARUP_RESOURCE_KIND`, `TEARUP_RESOURCE_ID` and `TEARUP_RESOURCE_DATA` set.
+    pub cleanup: Vec<String>,
+}
+
+impl Resource {
+    pub fn new(kind: impl Into<String>, id: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            id: id.into(),
+            data: String::new(),
+            cleanup: vec![],
+        }
+    }
+
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.data = data.into();
+        self
+    }
+
+    pub fn cleanup<S: Into<String>>(mut self, command: impl IntoIterator<Item = S>) -> Self {
+        self.cleanup = command.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// A recorded resource.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    path: PathBuf,
+    resource: Resource,
+    /// The test process that recorded it.
+    pid: u32,
+}
+
+impl Entry {
+    pub fn resource(&self) -> &Resource {
+        &self.resource
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Whether the test process that recorded it still runs, it may then still use the resource.
+    #[cfg(unix)]
+    pub fn is_running(&self) -> bool {
+        let pid = self.pid as libc::pid_t;
+        pid != 0 && unsafe { libc::kill(pid, 0) } == 0
+    }
+
+    #[cfg(not(unix))]
+    pub fn is_running(&self) -> bool {
+        false
+    }
+
+    /// The resource was destroyed.
+    pub fn remove(self) {
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            eprintln!("Failed to remove {}: {}", self.path.display(), err);
+        }
+    }
+
+    /// Run the cleanup command of the resource, then remove the entry.
+    pub fn clean(self) -> Result<(), String> {
+        let (program, args) = self
+            .resource
+            .cleanup
+            .split_first()
+            .ok_or("no cleanup command recorded")?;
+
+        let status = Command::new(program)
+            .args(args)
+            .env(KIND, &self.resource.kind)
+            .env(ID, &self.resource.id)
+            .env(DATA, &self.resource.data)
+            .status()
+            .map_err(|err| format!("failed to run {}: {}", program, err))?;
+        if !status.success() {
+            return Err(format!("{} exited with {}", program, status));
+        }
+
+        self.remove();
+        Ok(())
+    }
+
+    fn to_file(&self) -> String {
+        let Resource {
+            kind,
+            id,
+            data,
+            cleanup,
+        } = &self.resource;
+
+        let mut file = String::new();
+        let pid = self.pid.to_string();
+        let fields = [("kind", kind), ("id", id), ("data", data), ("pid", &pid)];
+        let cleanup = cleanup.iter().map(|arg| ("cleanup", arg));
+        for (key, value) in fields.into_iter().chain(cleanup) {
+            let _ = writeln!(file, "{}={}", key, escape(value));
+        }
+        file
+    }
+
+    fn from_file(path: PathBuf, content: &str) -> Option<Self> {
+        let mut resource = Resource::new("", "");
+        let mut pid = None;
+        for line in content.lines() {
+            let (key, value) = line.split_once('=')?;
+            let value = unescape(value);
+            match key {
+                "kind" => resource.kind = value,
+                "id" => resource.id = value,
+                "data" => resource.data = value,
+                "pid" => pid = value.parse().ok(),
+                "cleanup" => resource.cleanup.push(value),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            path,
+            resource,
+            pid: pid?,
+        })
+    }
+}
+
+/// One line per value.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some(escaped) => unescaped.push(escaped),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Ledger, Resource};
+
+    fn ledger(name: &str) -> Ledger {
+        let dir =
+            std::env::temp_dir().join(format!("tearup-ledger-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        Ledger::at(dir)
+    }
+
+    #[test]
+    fn it_records_resources_until_removed() {
+        let ledger = ledger("record");
+        let resource = Resource::new("postgres-db", "go_1")
+            .data("postgres://localhost\nwith \\ escapes")
+            .cleanup(["dropdb", "go_1"]);
+
+        let entry = ledger.record(resource.clone()).unwrap();
+        assert!(entry.is_running());
+        assert_eq!(ledger.entries().unwrap(), vec![entry]);
+        assert_eq!(ledger.entries().unwrap()[0].resource(), &resource);
+
+        ledger.entries().unwrap().remove(0).remove();
+        assert!(ledger.entries().unwrap().is_empty());
+        std::fs::remove_dir_all(ledger.dir()).unwrap();
+    }
+
+    #[test]
+    fn it_keeps_apart_the_resources_with_the_same_file_name() {
+        let ledger = ledger("same");
+
+        let first = ledger.record(Resource::new("db", "a/b")).unwrap();
+        let second = ledger.record(Resource::new("db", "a:b")).unwrap();
+        let again = ledger.record(Resource::new("db", "a:b")).unwrap();
+        let entries = ledger.entries().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!([first, second, again]
+            .iter()
+            .all(|entry| entries.contains(entry)));
+        std::fs::remove_dir_all(ledger.dir()).unwrap();
+    }
+
+    #[test]
+    fn it_skips_the_unreadable_entries() {
+        let ledger = ledger("unreadable");
+
+        let entry = ledger.record(Resource::new("db", "a")).unwrap();
+        std::fs::create_dir(ledger.dir().join("db-b.entry")).unwrap();
+        assert_eq!(ledger.entries().unwrap(), vec![entry]);
+        std::fs::remove_dir_all(ledger.dir()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_cleans_with_the_recorded_command() {
+        let ledger = ledger("clean");
+        let cleaned = ledger.dir().join("cleaned");
+        let cleanup = format!(
+            "echo \"$TEARUP_RESOURCE_KIND $TEARUP_RESOURCE_ID $TEARUP_RESOURCE_DATA\" > {}",
+            cleaned.display()
+        );
+
+        let entry = ledger
+            .record(
+                Resource::new("file", "a")
+                    .data("b")
+                    .cleanup(["sh", "-c", &cleanup]),
+            )
+            .unwrap();
+        entry.clean().unwrap();
+        assert_eq!(std::fs::read_to_string(&cleaned).unwrap(), "file a b\n");
+        assert!(ledger.entries().unwrap().is_empty());
+
+        let failing = ledger
+            .record(Resource::new("file", "c").cleanup(["false"]))
+            .unwrap();
+        assert!(failing.clean().is_err());
+        let missing = ledger.record(Resource::new("file", "d")).unwrap();
+        assert_eq!(
+            missing.clean(),
+            Err("no cleanup command recorded".to_owned())
+        );
+        assert_eq!(ledger.entries().unwrap().len(), 2);
+        std::fs::remove_dir_all(ledger.dir()).unwrap();
+    }
+}
@@ -1,18 +1,64 @@
 #[cfg(feature = "async")]
 pub use async_trait::async_trait;
+#[cfg(feature = "proptest")]
+pub use proptest;
+// Used by the tests generated for these runtimes
+#[cfg(feature = "async-std")]
+#[doc(hidden)]
+pub use async_std as __async_std;
+#[cfg(feature = "smol")]
+#[doc(hidden)]
+pub use smol as __smol;
 use std::time::Duration;
 pub use tearup_macro::{tearup, tearup_test};
+#[cfg(feature = "proptest")]
+pub use tearup_macro::tearup_proptest;
 
+#[cfg(feature = "async")]
+mod adapter;
+#[cfg(feature = "async")]
+pub use adapter::*;
+#[cfg(feature = "criterion")]
+pub mod bench;
+mod concurrency;
+pub use concurrency::{ConcurrencyWait, ConcurrencyWaits, HeldSlots, SlotScope};
 mod context;
 pub use context::*;
 mod context_combinator;
 pub use context_combinator::*;
 pub mod helper;
+mod interrupt;
+pub use interrupt::*;
+pub mod isolate;
+mod leak_check;
+pub use leak_check::*;
+pub mod ledger;
+mod locks;
+#[cfg(feature = "tracing")]
+mod log_capture;
+#[cfg(feature = "tracing")]
+pub use log_capture::*;
+mod observer;
+pub use observer::*;
+mod outcome;
+pub use outcome::*;
+mod pool;
+pub use pool::*;
+#[cfg(feature = "proptest")]
+mod prop;
+#[cfg(feature = "proptest")]
+pub use prop::*;
+#[cfg(feature = "async")]
+mod runtime;
+mod serial;
+pub use serial::*;
 mod shared_context;
 pub use shared_context::*;
+mod timings;
 
 #[derive(PartialEq, Debug)]
 pub struct TimeoutError {
     pub duration: Duration,
-    pub ready_checks_interval: Duration,
+    /// `None` for the gates without polling, since 0.4.
+    pub ready_checks_interval: Option<Duration>,
 }
@@ -0,0 +1,120 @@
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    sync::{Condvar, Mutex},
+};
+
+/// Named locks taken all at once, so two tests waiting for each other's keys can't deadlock.
+/// Not tied to a thread: the async adapters setup and teardown on different threads.
+pub(crate) struct KeyLocks {
+    held: Mutex<Option<HashSet<String>>>,
+    released: Condvar,
+}
+
+impl KeyLocks {
+    pub(crate) const fn new() -> Self {
+        Self {
+            held: Mutex::new(None),
+            released: Condvar::new(),
+        }
+    }
+
+    pub(crate) fn lock<K: AsRef<str>>(&self, keys: &[K]) {
+        let mut held = self.held.lock().unwrap();
+        loop {
+            let held_keys = held.get_or_insert_with(HashSet::new);
+            if keys.iter().all(|key| !held_keys.contains(key.as_ref())) {
+                held_keys.extend(keys.iter().map(|key| key.as_ref().to_owned()));
+                return;
+            }
+            held = self.released.wait(held).unwrap();
+        }
+    }
+
+    pub(crate) fn unlock<K: AsRef<str>>(&self, keys: &[K]) {
+        let mut held = self.held.lock().unwrap();
+        if let Some(held_keys) = held.as_mut() {
+            for key in keys {
+                held_keys.remove(key.as_ref());
+            }
+        }
+        self.released.notify_all();
+    }
+}
+
+/// An exclusive lock on a file shared by the processes of the machine, released on drop.
+/// On unix the lock goes away with the process, elsewhere a crashed process leaves the file locked.
+pub(crate) struct FileLock {
+    #[cfg_attr(unix, allow(dead_code))]
+    path: PathBuf,
+    _file: File,
+}
+
+impl FileLock {
+    /// `None` when another lock is held on the file.
+    pub(crate) fn try_acquire(path: &Path) -> io::Result<Option<Self>> {
+        Ok(lock_file(path, false)?.map(|file| Self {
+            path: path.to_owned(),
+            _file: file,
+        }))
+    }
+
+    /// Wait for the other locks on the file to be released.
+    pub(crate) fn acquire(path: &Path) -> io::Result<Self> {
+        loop {
+            if let Some(file) = lock_file(path, true)? {
+                return Ok(Self {
+                    path: path.to_owned(),
+                    _file: file,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn lock_file(path: &Path, wait: bool) -> io::Result<Option<File>> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(path)?;
+    let operation = match wait {
+        true => libc::LOCK_EX,
+        false => libc::LOCK_EX | libc::LOCK_NB,
+    };
+    match unsafe { libc::flock(file.as_raw_fd(), operation) } {
+        0 => Ok(Some(file)),
+        _ => match io::Error::last_os_error() {
+            err if err.raw_os_error() == Some(libc::EWOULDBLOCK) => Ok(None),
+            // Interrupted by a signal, the caller tries again
+            err if err.raw_os_error() == Some(libc::EINTR) => Ok(None),
+            err => Err(err),
+        },
+    }
+}
+
+#[cfg(not(unix))]
+fn lock_file(path: &Path, wait: bool) -> io::Result<Option<File>> {
+    match OpenOptions::new().create_new(true).write(true).open(path) {
+        Ok(file) => Ok(Some(file)),
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            if wait {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(not(unix))]
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+use tracing::subscriber::DefaultGuard;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Buffers the `tracing` events of the test thread, printed only if the test fails.
+///
+/// Taken before the setup and dropped after the teardown with `#[tearup_test(DbContext, capture_logs = true)]`,
+/// it replaces the default subscriber meanwhile. The events of other threads,
+/// e.g. of a multi-thread runtime, go to the global subscriber.
+pub struct LogCapture {
+    logs: Logs,
+    _default: DefaultGuard,
+}
+
+impl LogCapture {
+    pub fn start() -> Self {
+        let logs = Logs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_writer(logs.clone())
+            .finish();
+
+        Self {
+            _default: tracing::subscriber::set_default(subscriber),
+            logs,
+        }
+    }
+
+    /// The events so far, formatted.
+    pub fn logs(&self) -> String {
+        String::from_utf8_lossy(&self.logs.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Drop for LogCapture {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            eprint!("{}", self.logs());
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl<'a> MakeWriter<'a> for Logs {
+    type Writer = Logs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+impl io::Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LogCapture;
+    use crate::{Context, ContextCombinator, SharedContext, TestName};
+
+    struct Logging;
+    impl Context for Logging {
+        fn setup(_shared_context: &mut SharedContext) -> Self {
+            tracing::info!("database ready");
+            Self {}
+        }
+
+        fn teardown(self, _shared_context: &mut SharedContext) {
+            tracing::info!("database dropped");
+        }
+    }
+
+    #[test]
+    fn it_captures_the_events_in_the_spans_of_the_steps() {
+        let capture = LogCapture::start();
+
+        let mut shared_context = SharedContext::default();
+        shared_context.register(TestName("log_capture::it_captures"));
+        let context = ContextCombinator::<Logging, Logging>::launch_setup(&mut shared_context);
+        context.launch_teardown(&mut shared_context);
+
+        let logs = capture.logs();
+        let lines = logs.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4, "{}", logs);
+        let combinator =
+            "context=\"tearup::context_combinator::ContextCombinator<tearup::log_capture::test::Logging, tearup::log_capture::test::Logging>\"";
+        let member =
+            "context=\"tearup::log_capture::test::Logging\" test=\"log_capture::it_captures\"";
+        assert!(
+            lines[0].contains(&format!("setup{{{} ", combinator)),
+            "{}",
+            logs
+        );
+        assert!(
+            lines[0].contains(&format!("}}:setup{{{}}}: ", member)),
+            "{}",
+            logs
+        );
+        assert!(lines[0].ends_with("database ready"));
+        assert!(
+            lines[3].contains(&format!("}}:teardown{{{}}}: ", member)),
+            "{}",
+            logs
+        );
+        assert!(lines[3].ends_with("database dropped"));
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    future::Future,
+    sync::{Arc, Once, RwLock},
+    time::{Duration, Instant},
+};
+
+use crate::{timings::Timings, TestOutcome};
+
+static OBSERVERS: RwLock<Vec<Arc<dyn TearupObserver>>> = RwLock::new(vec![]);
+static TIMINGS: Once = Once::new();
+
+/// Receives what tearup does, for every context with `add_observer`,
+/// or for one context with its `fn observers()`.
+///
+/// ```ignore
+/// struct SlowSetups;
+/// impl TearupObserver for SlowSetups {
+///     fn on_event(&self, event: &TearupEvent) {
+///         if let TearupEventKind::SetupFinished { duration } = event.kind {
+///             if duration > Duration::from_secs(1) {
+///                 eprintln!("{} took {:?} to setup", event.context, duration);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub trait TearupObserver: Send + Sync {
+    fn on_event(&self, event: &TearupEvent);
+}
+
+/// Observe every context of the process, e.g. from a `std::sync::Once` in your setups.
+pub fn add_observer(observer: impl TearupObserver + 'static) {
+    OBSERVERS.write().unwrap().push(Arc::new(observer));
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TearupEvent {
+    /// The type name of the context, the members of a combinator have their own events.
+    pub context: &'static str,
+    /// `None` out of the macros, e.g. in benchmarks.
+    pub test: Option<&'static str>,
+    pub kind: TearupEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TearupEventKind {
+    SetupStarted,
+    SetupFinished {
+        duration: Duration,
+    },
+    TestStarted,
+    TestFinished {
+        duration: Duration,
+        outcome: TestOutcome,
+    },
+    TeardownStarted,
+    TeardownFinished {
+        duration: Duration,
+    },
+}
+
+/// The path of the running test, registered in the shared context by the macros before the setup.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestName(pub &'static str);
+
+/// A step of the lifecycle in progress, its start is sent to the observers.
+///
+/// With the `tracing` feature, the step runs in a `setup`, `test` or `teardown` span
+/// with the `context` and the `test` as fields, the steps of the members of a combinator are nested in it.
+pub struct Observation {
+    context: &'static str,
+    test: Option<&'static str>,
+    observers: Vec<Arc<dyn TearupObserver>>,
+    started: Instant,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl Observation {
+    /// `observers` are the ones of the context, the global ones are added.
+    pub fn start(
+        context: &'static str,
+        test: Option<&'static str>,
+        observers: Vec<Arc<dyn TearupObserver>>,
+        kind: TearupEventKind,
+    ) -> Self {
+        TIMINGS.call_once(|| {
+            if let Some(timings) = Timings::from_env() {
+                OBSERVERS.write().unwrap().push(timings);
+            }
+        });
+
+        let mut all = OBSERVERS.read().unwrap().clone();
+        for observer in observers {
+            // Once even when shared by the members of a combinator
+            if !all.iter().any(|added| Arc::ptr_eq(added, &observer)) {
+                all.push(observer);
+            }
+        }
+
+        let observation = Self {
+            context,
+            test,
+            observers: all,
+            started: Instant::now(),
+            #[cfg(feature = "tracing")]
+            span: span(context, test, &kind),
+        };
+        observation.send(kind);
+        observation
+    }
+
+    /// Run the step in the span.
+    pub fn in_scope<T>(&self, step: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "tracing")]
+        {
+            self.span.in_scope(step)
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            step()
+        }
+    }
+
+    /// Poll the step in the span.
+    pub async fn instrument<F: Future>(&self, step: F) -> F::Output {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::Instrument::instrument(step, self.span.clone()).await
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            step.await
+        }
+    }
+
+    /// Send the end of the step, given how long it took.
+    pub fn finish(self, kind: impl FnOnce(Duration) -> TearupEventKind) {
+        if !self.observers.is_empty() {
+            self.send(kind(self.started.elapsed()));
+        }
+    }
+
+    fn send(&self, kind: TearupEventKind) {
+        let event = TearupEvent {
+            context: self.context,
+            test: self.test,
+            kind,
+        };
+        for observer in &self.observers {
+            observer.on_event(&event);
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn span(
+    context: &'static str,
+    test: Option<&'static str>,
+    kind: &TearupEventKind,
+) -> tracing::Span {
+    let test = test.unwrap_or_default();
+    match kind {
+        TearupEventKind::SetupStarted => tracing::info_span!("setup", context, test),
+        TearupEventKind::TestStarted => tracing::info_span!("test", context, test),
+        _ => tracing::info_span!("teardown", context, test),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::{Observation, TearupEvent, TearupEventKind, TearupObserver};
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<TearupEvent>>);
+    impl TearupObserver for Recorder {
+        fn on_event(&self, event: &TearupEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn it_sends_the_start_and_the_end_of_a_step() {
+        let recorder = Arc::new(Recorder::default());
+
+        let observation = Observation::start(
+            "Observed",
+            Some("observer::it_sends"),
+            vec![recorder.clone()],
+            TearupEventKind::SetupStarted,
+        );
+        observation.finish(|duration| TearupEventKind::SetupFinished { duration });
+
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0],
+            TearupEvent {
+                context: "Observed",
+                test: Some("observer::it_sends"),
+                kind: TearupEventKind::SetupStarted
+            }
+        );
+        assert!(matches!(
+            events[1].kind,
+            TearupEventKind::SetupFinished { .. }
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+use std::any::Any;
+
+/// How the test went, registered in the shared context right before the teardown.
+///
+/// ```ignore
+/// fn teardown(self, shared_context: &mut SharedContext) {
+///     if let Some(TestOutcome::Failed(reason)) = shared_context.get() { ... }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestOutcome {
+    Passed,
+    /// With the panic message.
+    Failed(String),
+}
+
+impl TestOutcome {
+    /// The outcome of a `launch_test`.
+    pub fn of<T>(execution: &Result<T, Box<dyn Any + Send>>) -> Self {
+        match execution {
+            Ok(_) => TestOutcome::Passed,
+            Err(panic) => TestOutcome::Failed(panic_message(panic.as_ref())),
+        }
+    }
+
+    pub fn failed(&self) -> bool {
+        matches!(self, TestOutcome::Failed(_))
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "test panicked".to_owned()
+    }
+}
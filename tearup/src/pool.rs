@@ -0,0 +1,270 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{Arc, Mutex, Once},
+};
+
+use crate::{
+    concurrency::HeldSlots, Context, LeakCheck, Serial, SharedContext, TearupObserver, TestOutcome,
+};
+
+/// The idle instances of each `PooledContext`, with what their setup registered.
+static POOLS: Mutex<Option<HashMap<TypeId, Box<dyn Any + Send>>>> = Mutex::new(None);
+/// How to tear down each pool at the end of the process.
+static DRAINS: Mutex<Vec<fn()>> = Mutex::new(vec![]);
+static TEARDOWN_AT_EXIT: Once = Once::new();
+
+type Idle<C> = Vec<(C, SharedContext)>;
+
+/// A `Context` kept warm between tests, use it through `Pooled<C>`.
+///
+/// ```ignore
+/// impl PooledContext for DbContext {
+///     const POOL_SIZE: usize = 8;
+///
+///     fn reset(&mut self, _shared_context: &mut SharedContext) -> Result<(), String> {
+///         self.db.truncate_tables().map_err(|err| err.to_string())
+///     }
+/// }
+///
+/// type PooledDbContext = Pooled<DbContext>;
+/// ```
+pub trait PooledContext: Context + Send + 'static {
+    /// Idle instances kept for the next tests, the extra ones are torn down.
+    const POOL_SIZE: usize = 4;
+
+    /// Clean the instance after each test, even a failed one, e.g. truncate tables.
+    /// An error, or a panic, tears it down instead of giving it back to the pool.
+    fn reset(&mut self, shared_context: &mut SharedContext) -> Result<(), String>;
+}
+
+/// Takes an idle `C` from its pool, or sets up a new one, and gives it back reset after the test.
+/// The values registered by its setup are registered again for each test.
+///
+/// The idle instances are torn down when the process exits, on unix only.
+/// Use `Async<Pooled<C>>` in async tests.
+pub struct Pooled<C: PooledContext> {
+    context: C,
+    registered: SharedContext,
+}
+
+impl<C: PooledContext> Context for Pooled<C> {
+    /// The idle instances keep their resources open between the tests.
+    const LEAK_CHECK: Option<LeakCheck> = Some(LeakCheck::Off);
+
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        let (context, registered) = match take_idle::<C>() {
+            Some(idle) => idle,
+            None => {
+                // The instance goes back to the pool, its slot is kept until its teardown
+                let scope = shared_context.get::<HeldSlots>();
+                shared_context.register(HeldSlots::detached());
+                let before = shared_context.clone();
+                let context = C::launch_setup(shared_context);
+                let registered = shared_context.registered_since(&before);
+                if let Some(scope) = scope {
+                    shared_context.register(scope);
+                }
+                (context, registered)
+            }
+        };
+        shared_context.merge(&registered);
+
+        Self {
+            context,
+            registered,
+        }
+    }
+
+    fn teardown(self, shared_context: &mut SharedContext) {
+        let Self {
+            mut context,
+            mut registered,
+        } = self;
+
+        let reset = catch_unwind(AssertUnwindSafe(|| context.reset(shared_context)));
+        match reset {
+            Ok(Ok(())) => {
+                if let Some((context, mut registered)) = give_back(context, registered) {
+                    context.launch_teardown(&mut registered);
+                }
+            }
+            Ok(Err(reason)) => {
+                eprintln!(
+                    "Failed to reset {}, torn down: {}",
+                    std::any::type_name::<C>(),
+                    reason
+                );
+                registered.merge(shared_context);
+                registered.register(HeldSlots::detached());
+                context.launch_teardown(&mut registered);
+            }
+            Err(_) => {
+                registered.merge(shared_context);
+                registered.register(HeldSlots::detached());
+                context.launch_teardown(&mut registered);
+            }
+        }
+    }
+
+    fn serial() -> Vec<Serial> {
+        C::serial()
+    }
+
+    fn observers() -> Vec<Arc<dyn TearupObserver>> {
+        C::observers()
+    }
+
+    fn fallback_teardown(outcome: &TestOutcome) -> bool {
+        C::fallback_teardown(outcome)
+    }
+}
+
+fn take_idle<C: PooledContext>() -> Option<(C, SharedContext)> {
+    let mut pools = POOLS.lock().unwrap();
+    let pool = pools.as_mut()?.get_mut(&TypeId::of::<C>())?;
+    pool.downcast_mut::<Idle<C>>()?.pop()
+}
+
+/// The instance back when the pool is full.
+fn give_back<C: PooledContext>(
+    context: C,
+    registered: SharedContext,
+) -> Option<(C, SharedContext)> {
+    let mut pools = POOLS.lock().unwrap();
+    let pool = pools
+        .get_or_insert_with(HashMap::new)
+        .entry(TypeId::of::<C>())
+        .or_insert_with(|| {
+            DRAINS.lock().unwrap().push(drain::<C>);
+            Box::new(Idle::<C>::new())
+        })
+        .downcast_mut::<Idle<C>>()
+        .expect("pools are stored by type");
+
+    if pool.len() >= C::POOL_SIZE {
+        return Some((context, registered));
+    }
+    pool.push((context, registered));
+    drop(pools);
+
+    TEARDOWN_AT_EXIT.call_once(teardown_at_exit);
+    None
+}
+
+/// Tear down the idle instances of `C`.
+fn drain<C: PooledContext>() {
+    let idle = {
+        let mut pools = POOLS.lock().unwrap();
+        let pool = pools
+            .as_mut()
+            .and_then(|pools| pools.get_mut(&TypeId::of::<C>()))
+            .and_then(|pool| pool.downcast_mut::<Idle<C>>());
+        pool.map(std::mem::take).unwrap_or_default()
+    };
+
+    for (context, mut registered) in idle {
+        let teardown = catch_unwind(AssertUnwindSafe(|| {
+            context.launch_teardown(&mut registered)
+        }));
+        if teardown.is_err() {
+            eprintln!("Failed to tear down {}", std::any::type_name::<C>());
+        }
+    }
+}
+
+#[cfg(unix)]
+fn teardown_at_exit() {
+    extern "C" fn at_exit() {
+        let drains = DRAINS.lock().unwrap().clone();
+        for drain in drains {
+            drain();
+        }
+    }
+
+    if unsafe { libc::atexit(at_exit) } != 0 {
+        eprintln!("The pooled contexts won't be torn down at exit");
+    }
+}
+
+#[cfg(not(unix))]
+fn teardown_at_exit() {}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{drain, Pooled, PooledContext};
+    use crate::{Context, SharedContext, TestOutcome};
+
+    #[derive(Clone)]
+    struct Instance(usize);
+
+    static SETUPS: AtomicUsize = AtomicUsize::new(0);
+    static RESETS: AtomicUsize = AtomicUsize::new(0);
+    static TEARDOWNS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Reusable(usize);
+    impl Context for Reusable {
+        fn setup(shared_context: &mut SharedContext) -> Self {
+            let instance = SETUPS.fetch_add(1, Ordering::SeqCst);
+            shared_context.register(Instance(instance));
+            Self(instance)
+        }
+
+        fn teardown(self, _shared_context: &mut SharedContext) {
+            TEARDOWNS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    impl PooledContext for Reusable {
+        const POOL_SIZE: usize = 1;
+
+        fn reset(&mut self, shared_context: &mut SharedContext) -> Result<(), String> {
+            RESETS.fetch_add(1, Ordering::SeqCst);
+            match shared_context.get::<TestOutcome>() {
+                Some(TestOutcome::Failed(reason)) if reason == "corrupted" => Err(reason),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    fn run_test(outcome: TestOutcome) -> usize {
+        let mut shared_context = SharedContext::default();
+        let context = Pooled::<Reusable>::setup(&mut shared_context);
+        let instance = shared_context.get::<Instance>().unwrap().0;
+        assert_eq!(instance, context.context.0);
+
+        shared_context.register(outcome);
+        context.teardown(&mut shared_context);
+        instance
+    }
+
+    #[test]
+    fn it_reuses_reset_instances() {
+        let first = run_test(TestOutcome::Passed);
+        let second = run_test(TestOutcome::Failed("flaky".to_owned()));
+        assert_eq!(first, second);
+        assert_eq!(SETUPS.load(Ordering::SeqCst), 1);
+        assert_eq!(RESETS.load(Ordering::SeqCst), 2);
+
+        // Failed to reset
+        run_test(TestOutcome::Failed("corrupted".to_owned()));
+        assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 1);
+        let third = run_test(TestOutcome::Passed);
+        assert_ne!(third, first);
+
+        // Two at once, the pool keeps one
+        let mut first_shared_context = SharedContext::default();
+        let first = Pooled::<Reusable>::setup(&mut first_shared_context);
+        let mut second_shared_context = SharedContext::default();
+        let second = Pooled::<Reusable>::setup(&mut second_shared_context);
+        first.teardown(&mut first_shared_context);
+        second.teardown(&mut second_shared_context);
+        assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 2);
+
+        drain::<Reusable>();
+        assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,44 @@
+use std::fmt::{Debug, Display, Formatter, Result};
+
+/// Registered in the `SharedContext` before the teardown when a `#[tearup_proptest]` fails,
+/// so the context can dump what it knows about the failing case.
+#[derive(Clone, Debug)]
+pub struct PropFailure {
+    pub context: &'static str,
+    /// The fixtures of the minimal failing case with their `Debug`, or their type when they have none.
+    pub fixtures: String,
+    pub reason: String,
+}
+
+impl Display for PropFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, "{}", self.reason)?;
+        write!(f, "fixtures from {}: [{}]", self.context, self.fixtures)
+    }
+}
+
+/// Picks `DebugFixture` when the fixture has a `Debug`, `OpaqueFixture` otherwise: `(&&Fixture(&value)).describe()`.
+#[doc(hidden)]
+pub struct Fixture<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait DebugFixture {
+    fn describe(&self) -> String;
+}
+
+impl<T: Debug> DebugFixture for &Fixture<'_, T> {
+    fn describe(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[doc(hidden)]
+pub trait OpaqueFixture {
+    fn describe(&self) -> String;
+}
+
+impl<T> OpaqueFixture for Fixture<'_, T> {
+    fn describe(&self) -> String {
+        format!("<{}>", std::any::type_name::<T>())
+    }
+}
@@ -0,0 +1,96 @@
+//! What the async helpers need from the runtime chosen with the features.
+//! async-std and smol timers and blocking pools run on their own threads so they are preferred inside async code:
+//! they work under any runtime. To block on async code tokio is preferred: its contexts need a tokio runtime.
+use std::time::Duration;
+
+#[cfg(not(any(feature = "tokio", feature = "async-std", feature = "smol")))]
+compile_error!("The 'async' feature needs a runtime: turn on 'tokio', 'async-std' or 'smol'.");
+
+#[cfg(all(feature = "native-async", tearup_before_1_75))]
+compile_error!("The 'native-async' feature needs Rust 1.75+ for `async fn` in traits.");
+
+#[cfg(feature = "async-std")]
+pub(crate) async fn sleep(duration: Duration) {
+    async_std::task::sleep(duration).await;
+}
+
+#[cfg(all(feature = "smol", not(feature = "async-std")))]
+pub(crate) async fn sleep(duration: Duration) {
+    smol::Timer::after(duration).await;
+}
+
+#[cfg(all(feature = "tokio", not(any(feature = "async-std", feature = "smol"))))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Run blocking code, e.g. a sync `Context`, without blocking the async runtime.
+#[cfg(feature = "async-std")]
+pub(crate) async fn spawn_blocking<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    async_std::task::spawn_blocking(f).await
+}
+
+#[cfg(all(feature = "smol", not(feature = "async-std")))]
+pub(crate) async fn spawn_blocking<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    smol::unblock(f).await
+}
+
+#[cfg(all(feature = "tokio", not(any(feature = "async-std", feature = "smol"))))]
+pub(crate) async fn spawn_blocking<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))
+}
+
+/// Drives async code from sync code.
+/// With tokio it owns a multi thread runtime so the tasks spawned during the setup keep running during the test.
+#[cfg(feature = "tokio")]
+pub(crate) struct BlockingRuntime(tokio::runtime::Runtime);
+
+#[cfg(feature = "tokio")]
+impl BlockingRuntime {
+    pub(crate) fn new() -> Self {
+        Self(
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build the tokio runtime"),
+        )
+    }
+
+    pub(crate) fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
+        self.0.block_on(future)
+    }
+}
+
+#[cfg(not(feature = "tokio"))]
+pub(crate) struct BlockingRuntime;
+
+#[cfg(not(feature = "tokio"))]
+impl BlockingRuntime {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+
+    #[cfg(feature = "async-std")]
+    pub(crate) fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
+        async_std::task::block_on(future)
+    }
+
+    #[cfg(all(feature = "smol", not(feature = "async-std")))]
+    pub(crate) fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
+        smol::block_on(future)
+    }
+}
@@ -0,0 +1,181 @@
+use crate::locks::{FileLock, KeyLocks};
+
+static SERIAL_LOCKS: KeyLocks = KeyLocks::new();
+
+/// Tests of the same group never overlap, from before the setup to after the teardown.
+///
+/// Pick it per test with `#[tearup_test(DbContext, serial = "db")]`,
+/// or for every test using a context:
+///
+/// ```ignore
+/// impl Context for DaemonContext {
+///     fn serial() -> Vec<Serial> {
+///         vec![Serial::group("daemon").cross_process()]
+///     }
+///     ...
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Serial {
+    name: String,
+    cross_process: bool,
+}
+
+impl Serial {
+    /// Within the test binary.
+    pub fn group(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            cross_process: false,
+        }
+    }
+
+    /// Also across the test processes of the machine, e.g. with cargo-nextest,
+    /// through a lock file under `<temp dir>/tearup-serial`.
+    pub fn cross_process(mut self) -> Self {
+        self.cross_process = true;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The groups of a test, held by the macros around the setup, the test and the teardown.
+pub struct SerialGuard {
+    names: Vec<String>,
+    files: Vec<FileLock>,
+}
+
+impl SerialGuard {
+    /// Wait for the other tests of the groups to finish.
+    pub fn acquire(groups: impl IntoIterator<Item = Serial>) -> Self {
+        let mut groups = groups.into_iter().collect::<Vec<_>>();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        groups.dedup_by(|duplicate, kept| {
+            let same = duplicate.name == kept.name;
+            kept.cross_process |= same && duplicate.cross_process;
+            same
+        });
+
+        let names = groups
+            .iter()
+            .map(|group| group.name.clone())
+            .collect::<Vec<_>>();
+        SERIAL_LOCKS.lock(&names);
+
+        // Built first so that a panic below releases what is held
+        let mut guard = Self {
+            names,
+            files: vec![],
+        };
+        for group in groups.iter().filter(|group| group.cross_process) {
+            let dir = std::env::temp_dir().join("tearup-serial");
+            let path = dir.join(format!("{}.lock", file_name(&group.name)));
+
+            let lock = std::fs::create_dir_all(&dir).and_then(|_| FileLock::acquire(&path));
+            match lock {
+                Ok(lock) => guard.files.push(lock),
+                Err(err) => panic!("Failed to lock {}: {}", path.display(), err),
+            }
+        }
+        guard
+    }
+}
+
+impl Drop for SerialGuard {
+    fn drop(&mut self) {
+        self.files.clear();
+        SERIAL_LOCKS.unlock(&self.names);
+    }
+}
+
+/// Safe as a file name on every platform.
+pub(crate) fn file_name(group: &str) -> String {
+    group
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{Arc, Barrier, Mutex},
+        thread::{sleep, spawn},
+        time::Duration,
+    };
+
+    use super::{file_name, Serial, SerialGuard};
+    use crate::locks::FileLock;
+
+    fn overlaps(groups: [Vec<Serial>; 2]) -> bool {
+        let running = Arc::new(Mutex::new(0));
+        let overlapped = Arc::new(Mutex::new(false));
+        let barrier = Arc::new(Barrier::new(2));
+
+        let tests = groups.map(|groups| {
+            let (running, overlapped, barrier) =
+                (running.clone(), overlapped.clone(), barrier.clone());
+            spawn(move || {
+                barrier.wait();
+                let _guard = SerialGuard::acquire(groups);
+                *running.lock().unwrap() += 1;
+                sleep(Duration::from_millis(50));
+                *overlapped.lock().unwrap() |= *running.lock().unwrap() > 1;
+                *running.lock().unwrap() -= 1;
+            })
+        });
+        for test in tests {
+            test.join().unwrap();
+        }
+
+        let overlapped = *overlapped.lock().unwrap();
+        overlapped
+    }
+
+    #[test]
+    fn it_runs_a_group_one_at_a_time() {
+        assert!(!overlaps([
+            vec![Serial::group("serial-test-a")],
+            vec![
+                Serial::group("serial-test-b"),
+                Serial::group("serial-test-a")
+            ],
+        ]));
+    }
+
+    #[test]
+    fn it_runs_other_groups_concurrently() {
+        assert!(overlaps([
+            vec![Serial::group("serial-test-c")],
+            vec![Serial::group("serial-test-d")],
+        ]));
+        assert!(overlaps([vec![], vec![]]));
+    }
+
+    #[test]
+    fn it_locks_a_file_across_processes() {
+        let path = std::env::temp_dir()
+            .join("tearup-serial")
+            .join("serial-test-file.lock");
+
+        let guard = SerialGuard::acquire([
+            Serial::group("serial-test-file"),
+            Serial::group("serial-test-file").cross_process(),
+        ]);
+        assert!(FileLock::try_acquire(&path).unwrap().is_none());
+
+        drop(guard);
+        assert!(FileLock::try_acquire(&path).unwrap().is_some());
+    }
+
+    #[test]
+    fn it_names_files_after_groups() {
+        assert_eq!(file_name("db/main schema"), "db_main_schema");
+    }
+}
@@ -10,18 +10,40 @@ pub struct TimeoutError {
     pub ready_checks_interval: Duration,
 }
 
-type AnyMap = Map<dyn CloneAny>;
+/// Values are `Send` so the context can move between the sync and the async worlds, see `Blocking` and `Async`.
+/// Since 0.4, a breaking change for the sync contexts registering `!Send` values.
+type AnyMap = Map<dyn CloneAny + Send>;
 
+#[derive(Clone)]
 pub struct SharedContext(AnyMap);
 
 impl SharedContext {
-    pub fn register<T: 'static + Clone>(&mut self, value: T) {
+    /// `T` must be `Send` since 0.4, for the adapters moving the values between threads.
+    pub fn register<T: 'static + Send + Clone>(&mut self, value: T) {
         self.0.insert(value);
     }
 
-    pub fn get<T: 'static + Clone>(&mut self) -> Option<T> {
+    pub fn get<T: 'static + Send + Clone>(&mut self) -> Option<T> {
         self.0.get::<T>().cloned()
     }
+
+    /// Register all the values of the other context, replacing the ones of the same type.
+    pub(crate) fn merge(&mut self, other: &SharedContext) {
+        self.0.extend(other.0.as_raw().values().cloned());
+    }
+
+    /// The values whose type was not registered in `before`.
+    pub(crate) fn registered_since(&self, before: &SharedContext) -> SharedContext {
+        let mut registered = AnyMap::new();
+        registered.extend(
+            self.0
+                .as_raw()
+                .iter()
+                .filter(|(type_id, _)| !before.0.as_raw().contains_key(type_id))
+                .map(|(_, value)| value.clone()),
+        );
+        Self(registered)
+    }
 }
 
 impl Default for SharedContext {
@@ -36,6 +58,8 @@ pub mod asyncc {
     use std::sync::Arc;
     use tokio::sync::Mutex;
 
+    use super::SharedContext;
+
     type AnymapSend = Map<dyn CloneAny + Send>;
 
     #[derive(Clone)]
@@ -50,6 +74,17 @@ pub mod asyncc {
         pub async fn get<T: 'static + Send + Clone>(&mut self) -> Option<T> {
             self.0.lock().await.get::<T>().cloned()
         }
+
+        /// A sync copy of all the registered values.
+        pub(crate) async fn snapshot(&self) -> SharedContext {
+            SharedContext(self.0.lock().await.clone())
+        }
+
+        /// Register all the values of the sync context, replacing the ones of the same type.
+        pub(crate) async fn merge(&self, other: SharedContext) {
+            let mut map = self.0.lock().await;
+            map.extend(other.0.as_raw().values().cloned());
+        }
     }
 
     impl Default for AsyncSharedContext {
@@ -0,0 +1,452 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread::ThreadId,
+    time::Duration,
+};
+
+use crate::{TearupEvent, TearupEventKind, TearupObserver};
+
+/// Set to `1` to print the setup and teardown durations of each context when the test binary exits.
+const TIMINGS: &str = "TEARUP_TIMINGS";
+/// Seconds above which a setup is reported as slow.
+const SLOW_SETUP: &str = "TEARUP_SLOW_SETUP";
+
+/// The durations of the contexts of the test binary, added to the observers by the first setup
+/// when `TEARUP_TIMINGS=1` or `TEARUP_SLOW_SETUP` is set.
+///
+/// At exit, on unix, a table goes to stderr and the JSON to `target/tearup/timings-<test binary>.json`.
+/// The time of a combinator is split between its members, its own time is the remainder.
+pub(crate) struct Timings {
+    report: bool,
+    slow_setup: Option<Duration>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    contexts: HashMap<&'static str, ContextTimings>,
+    /// The setups and teardowns in progress of each test, the members of a combinator on top of it.
+    running: HashMap<Nesting, Vec<Running>>,
+}
+
+/// Whose setups and teardowns nest, the ones of a test even across threads, e.g. `Async<C>` on the blocking pool.
+/// Without a test, e.g. in a benchmark, the ones of a thread.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Nesting {
+    Test(&'static str),
+    Thread(ThreadId),
+}
+
+impl Nesting {
+    fn of(event: &TearupEvent) -> Self {
+        match event.test {
+            Some(test) => Self::Test(test),
+            None => Self::Thread(std::thread::current().id()),
+        }
+    }
+}
+
+struct Running {
+    context: &'static str,
+    /// Spent in the members.
+    children: Duration,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct ContextTimings {
+    setups: u32,
+    setup_total: Duration,
+    /// Without the setups of the members.
+    setup_own: Duration,
+    setup_max: Duration,
+    slow_setups: u32,
+    teardowns: u32,
+    teardown_total: Duration,
+    teardown_own: Duration,
+    members: Vec<&'static str>,
+}
+
+impl Timings {
+    /// `None` when neither reporting nor warning.
+    pub(crate) fn from_env() -> Option<Arc<Self>> {
+        let report = std::env::var(TIMINGS).as_deref() == Ok("1");
+        let slow_setup = crate::helper::secs_var(SLOW_SETUP);
+        if !report && slow_setup.is_none() {
+            return None;
+        }
+
+        let timings = Arc::new(Self {
+            report,
+            slow_setup,
+            state: Mutex::default(),
+        });
+        if report {
+            report_at_exit(timings.clone());
+        }
+        Some(timings)
+    }
+
+    fn started(&self, event: &TearupEvent) {
+        let mut state = self.state.lock().unwrap();
+        let nesting = Nesting::of(event);
+        state.running.entry(nesting).or_default().push(Running {
+            context: event.context,
+            children: Duration::ZERO,
+        });
+    }
+
+    fn finished(&self, event: &TearupEvent, duration: Duration, setup: bool) {
+        let mut state = self.state.lock().unwrap();
+        let nesting = Nesting::of(event);
+        let running = state.running.entry(nesting).or_default();
+        let children = match running.pop() {
+            Some(finished) if finished.context == event.context => finished.children,
+            _ => Duration::ZERO,
+        };
+        let parent = running.last_mut().map(|parent| {
+            parent.children += duration;
+            parent.context
+        });
+        if running.is_empty() {
+            state.running.remove(&nesting);
+        }
+
+        let slow = setup && matches!(self.slow_setup, Some(slow) if duration > slow);
+        let own = duration.saturating_sub(children);
+        let timings = state.contexts.entry(event.context).or_default();
+        match setup {
+            true => {
+                timings.setups += 1;
+                timings.setup_total += duration;
+                timings.setup_own += own;
+                timings.setup_max = timings.setup_max.max(duration);
+                timings.slow_setups += slow as u32;
+            }
+            false => {
+                timings.teardowns += 1;
+                timings.teardown_total += duration;
+                timings.teardown_own += own;
+            }
+        }
+        if let Some(parent) = parent {
+            let parent = state.contexts.entry(parent).or_default();
+            if !parent.members.contains(&event.context) {
+                parent.members.push(event.context);
+            }
+        }
+        drop(state);
+
+        if slow {
+            eprintln!(
+                "{} took {:?} to set up{}, over {}",
+                event.context,
+                duration,
+                event
+                    .test
+                    .map(|test| format!(" for {}", test))
+                    .unwrap_or_default(),
+                SLOW_SETUP
+            );
+        }
+    }
+
+    /// Slowest setups first.
+    fn sorted(&self) -> Vec<(&'static str, ContextTimings)> {
+        let state = self.state.lock().unwrap();
+        let mut contexts = state
+            .contexts
+            .iter()
+            .map(|(context, timings)| (*context, timings.clone()))
+            .collect::<Vec<_>>();
+        contexts.sort_by(|(a_name, a), (b_name, b)| {
+            b.setup_total.cmp(&a.setup_total).then(a_name.cmp(b_name))
+        });
+        contexts
+    }
+
+    fn table(&self) -> String {
+        let contexts = self.sorted();
+        let width = contexts
+            .iter()
+            .map(|(context, _)| context.len())
+            .chain(Some("context".len()))
+            .max()
+            .unwrap_or_default();
+
+        let mut table = format!(
+            "{:<width$}  {:>6}  {:>10}  {:>10}  {:>10}  {:>5}  {:>10}  {:>10}\n",
+            "context",
+            "setups",
+            "total",
+            "own",
+            "max",
+            "slow",
+            "teardown",
+            "own",
+            width = width
+        );
+        for (context, timings) in contexts {
+            let _ = writeln!(
+                table,
+                "{:<width$}  {:>6}  {:>10}  {:>10}  {:>10}  {:>5}  {:>10}  {:>10}",
+                context,
+                timings.setups,
+                millis(timings.setup_total),
+                millis(timings.setup_own),
+                millis(timings.setup_max),
+                timings.slow_setups,
+                millis(timings.teardown_total),
+                millis(timings.teardown_own),
+                width = width
+            );
+        }
+        table
+    }
+
+    fn json(&self) -> String {
+        let contexts = self
+            .sorted()
+            .into_iter()
+            .map(|(context, timings)| {
+                let members = timings
+                    .members
+                    .iter()
+                    .map(|member| json_string(member))
+                    .collect::<Vec<_>>();
+                format!(
+                    "{{\"context\":{},\"setups\":{},\"setup_total_ms\":{},\"setup_own_ms\":{},\"setup_max_ms\":{},\"slow_setups\":{},\"teardowns\":{},\"teardown_total_ms\":{},\"teardown_own_ms\":{},\"members\":[{}]}}",
+                    json_string(context),
+                    timings.setups,
+                    as_millis(timings.setup_total),
+                    as_millis(timings.setup_own),
+                    as_millis(timings.setup_max),
+                    timings.slow_setups,
+                    timings.teardowns,
+                    as_millis(timings.teardown_total),
+                    as_millis(timings.teardown_own),
+                    members.join(",")
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let slow_setup = match self.slow_setup {
+            Some(slow_setup) => as_millis(slow_setup).to_string(),
+            None => "null".to_owned(),
+        };
+        format!(
+            "{{\"slow_setup_ms\":{},\"contexts\":[{}]}}\n",
+            slow_setup,
+            contexts.join(",")
+        )
+    }
+
+    fn report(&self) {
+        if !self.report || self.state.lock().unwrap().contexts.is_empty() {
+            return;
+        }
+        eprint!("\n{}", self.table());
+
+        let path = report_path();
+        let written = std::fs::create_dir_all(crate::helper::tearup_dir())
+            .and_then(|_| std::fs::write(&path, self.json()));
+        match written {
+            Ok(()) => eprintln!("Timings written to {}", path.display()),
+            Err(err) => eprintln!("Failed to write {}: {}", path.display(), err),
+        }
+    }
+}
+
+impl TearupObserver for Timings {
+    fn on_event(&self, event: &TearupEvent) {
+        match event.kind {
+            TearupEventKind::SetupStarted | TearupEventKind::TeardownStarted => self.started(event),
+            TearupEventKind::SetupFinished { duration } => self.finished(event, duration, true),
+            TearupEventKind::TeardownFinished { duration } => self.finished(event, duration, false),
+            TearupEventKind::TestStarted | TearupEventKind::TestFinished { .. } => {}
+        }
+    }
+}
+
+fn report_path() -> PathBuf {
+    let binary = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| std::process::id().to_string());
+    crate::helper::tearup_dir().join(format!("timings-{}.json", binary))
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1}ms", as_millis(duration))
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+static REPORTED: Mutex<Option<Arc<Timings>>> = Mutex::new(None);
+
+#[cfg(unix)]
+fn report_at_exit(timings: Arc<Timings>) {
+    extern "C" fn at_exit() {
+        if let Some(timings) = REPORTED.lock().unwrap().take() {
+            timings.report();
+        }
+    }
+
+    *REPORTED.lock().unwrap() = Some(timings);
+    if unsafe { libc::atexit(at_exit) } != 0 {
+        eprintln!("The timings won't be reported at exit");
+    }
+}
+
+#[cfg(not(unix))]
+fn report_at_exit(_timings: Arc<Timings>) {}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{Arc, Mutex},
+        thread::spawn,
+        time::Duration,
+    };
+
+    use super::{json_string, Timings};
+    use crate::{TearupEvent, TearupEventKind, TearupObserver};
+
+    fn send(timings: &Timings, context: &'static str, kind: TearupEventKind) {
+        timings.on_event(&TearupEvent {
+            context,
+            test: Some("timings::test"),
+            kind,
+        });
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn it_splits_the_time_of_a_combinator_between_its_members() {
+        let timings = Timings {
+            report: false,
+            slow_setup: Some(ms(50)),
+            state: Mutex::default(),
+        };
+
+        send(&timings, "Both", TearupEventKind::SetupStarted);
+        send(&timings, "Db", TearupEventKind::SetupStarted);
+        let duration = ms(60);
+        send(&timings, "Db", TearupEventKind::SetupFinished { duration });
+        send(&timings, "Server", TearupEventKind::SetupStarted);
+        let duration = ms(30);
+        send(
+            &timings,
+            "Server",
+            TearupEventKind::SetupFinished { duration },
+        );
+        let duration = ms(100);
+        send(
+            &timings,
+            "Both",
+            TearupEventKind::SetupFinished { duration },
+        );
+        send(&timings, "Both", TearupEventKind::TeardownStarted);
+        let duration = ms(5);
+        send(
+            &timings,
+            "Both",
+            TearupEventKind::TeardownFinished { duration },
+        );
+
+        let contexts = timings.sorted();
+        let names = contexts.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        assert_eq!(names, vec!["Both", "Db", "Server"]);
+
+        let both = &contexts[0].1;
+        assert_eq!(both.setup_total, ms(100));
+        assert_eq!(both.setup_own, ms(10));
+        assert_eq!(both.slow_setups, 1);
+        assert_eq!(both.teardown_own, ms(5));
+        assert_eq!(both.members, vec!["Db", "Server"]);
+        assert_eq!(contexts[1].1.slow_setups, 1);
+        assert_eq!(contexts[2].1.slow_setups, 0);
+        assert!(timings.state.lock().unwrap().running.is_empty());
+
+        let table = timings.table();
+        assert!(table.starts_with("context  setups"), "{}", table);
+        assert!(
+            table.contains("\nBoth          1     100.0ms      10.0ms"),
+            "{}",
+            table
+        );
+
+        let json = timings.json();
+        assert!(json.starts_with("{\"slow_setup_ms\":50,\"contexts\":[{\"context\":\"Both\",\"setups\":1,\"setup_total_ms\":100,\"setup_own_ms\":10,"), "{}", json);
+        assert!(json.contains("\"members\":[\"Db\",\"Server\"]"), "{}", json);
+    }
+
+    #[test]
+    fn it_nests_the_events_without_test_by_thread() {
+        let timings = Arc::new(Timings {
+            report: false,
+            slow_setup: None,
+            state: Mutex::default(),
+        });
+        let send_without_test = |timings: &Timings, context, kind| {
+            timings.on_event(&TearupEvent {
+                context,
+                test: None,
+                kind,
+            })
+        };
+
+        send_without_test(&timings, "Outer", TearupEventKind::SetupStarted);
+        let other = timings.clone();
+        spawn(move || {
+            send_without_test(&other, "Other", TearupEventKind::SetupStarted);
+            let duration = ms(20);
+            send_without_test(&other, "Other", TearupEventKind::SetupFinished { duration });
+        })
+        .join()
+        .unwrap();
+        let duration = ms(50);
+        send_without_test(
+            &timings,
+            "Outer",
+            TearupEventKind::SetupFinished { duration },
+        );
+
+        let contexts = timings.sorted();
+        assert_eq!(contexts[0].0, "Outer");
+        assert_eq!(contexts[0].1.setup_own, ms(50));
+        assert!(contexts[0].1.members.is_empty());
+        assert!(timings.state.lock().unwrap().running.is_empty());
+    }
+
+    #[test]
+    fn it_escapes_json_strings() {
+        assert_eq!(
+            json_string("Pooled<\"a\\b\">\n"),
+            "\"Pooled<\\\"a\\\\b\\\">\\u000a\""
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use tearup::{tearup, Async, AsyncContextCombinator, Blocking, ContextCombinator};
+
+use crate::helper::{
+    AsyncFirstFullContext, AsyncSecondFullContext, FirstFullContext, FirstProof, FourthProof,
+    SecondFullContext, SecondProof, ThirdProof,
+};
+
+#[test]
+fn it_uses_an_async_context_in_a_sync_test() {
+    sync_test_with_async_context();
+}
+
+type BlockingFirst = Blocking<AsyncFirstFullContext>;
+#[tearup(BlockingFirst)]
+fn sync_test_with_async_context(a: FirstProof, b: SecondProof) {
+    assert_eq!(a.0, "first_proof");
+    assert_eq!(b.0, "second_proof");
+}
+
+#[tokio::test]
+async fn it_uses_a_sync_context_in_an_async_test() {
+    async_test_with_sync_context().await;
+}
+
+type AsyncFirst = Async<FirstFullContext>;
+#[tearup(AsyncFirst)]
+async fn async_test_with_sync_context(a: FirstProof, b: SecondProof) {
+    assert_eq!(a.0, "first_proof");
+    assert_eq!(b.0, "second_proof");
+}
+
+#[test]
+fn it_mixes_sync_and_async_contexts_in_a_sync_combinator() {
+    sync_mixed();
+}
+
+type SyncMixed = ContextCombinator<FirstFullContext, Blocking<AsyncSecondFullContext>>;
+#[tearup(SyncMixed)]
+fn sync_mixed(c: ThirdProof, d: FourthProof) {
+    assert_eq!(c.0, "ref_to_first_proof");
+    assert_eq!(d.0, "another_ref_to_second_proof");
+}
+
+#[tokio::test]
+async fn it_mixes_sync_and_async_contexts_in_an_async_combinator() {
+    async_mixed().await;
+}
+
+type AsyncMixed = AsyncContextCombinator<AsyncFirst, AsyncSecondFullContext>;
+#[tearup(AsyncMixed)]
+async fn async_mixed(c: ThirdProof, d: FourthProof) {
+    assert_eq!(c.0, "ref_to_first_proof");
+    assert_eq!(d.0, "another_ref_to_second_proof");
+}
+
+#[test]
+fn it_gives_the_values_of_an_async_context_to_the_next_sync_one() {
+    blocking_then_sync();
+}
+
+type BlockingThenSync = ContextCombinator<BlockingFirst, SecondFullContext>;
+#[tearup(BlockingThenSync)]
+fn blocking_then_sync(c: ThirdProof) {
+    assert_eq!(c.0, "ref_to_first_proof");
+}
+
+/// The adapter runs the tokio runtime when it is the only one.
+#[cfg(all(feature = "tokio", not(any(feature = "async-std", feature = "smol"))))]
+mod ticking {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread::sleep,
+        time::Duration,
+    };
+    use tearup::{tearup, AsyncContext, AsyncSharedContext, Blocking};
+
+    #[test]
+    fn it_keeps_the_runtime_alive_during_a_sync_test() {
+        spawned_task_keeps_running();
+    }
+
+    #[derive(Clone)]
+    struct Ticks(Arc<AtomicUsize>);
+
+    struct TickingContext;
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for TickingContext {
+        async fn setup(shared_context: AsyncSharedContext) -> Self {
+            let ticks = Arc::new(AtomicUsize::new(0));
+            let task_ticks = ticks.clone();
+            tokio::spawn(async move {
+                loop {
+                    task_ticks.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+            });
+            shared_context.register(Ticks(ticks)).await;
+            Self {}
+        }
+
+        async fn teardown(self, _shared_context: AsyncSharedContext) {}
+    }
+
+    type BlockingTicking = Blocking<TickingContext>;
+    #[tearup(BlockingTicking)]
+    fn spawned_task_keeps_running(ticks: Ticks) {
+        let before = ticks.0.load(Ordering::SeqCst);
+        sleep(Duration::from_millis(50));
+        assert!(ticks.0.load(Ordering::SeqCst) > before);
+    }
+}
@@ -61,7 +61,7 @@ impl Context for SecondContext {
 mod asyncc {
     use lazy_static::lazy_static;
     use std::time::{Duration, SystemTime};
-    use tearup::{async_trait, tearup, AsyncContext, AsyncContextCombinator, AsyncSharedContext};
+    use tearup::{tearup, AsyncContext, AsyncContextCombinator, AsyncSharedContext};
     use tokio::time::sleep;
 
     use crate::helper::{assert_async_order, AsyncCheckpoint};
@@ -87,8 +87,8 @@ mod asyncc {
     async fn sequential() {}
 
     pub struct FirstContext;
-    #[async_trait]
-    impl AsyncContext<'_> for FirstContext {
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for FirstContext {
         async fn setup(_shared_context: AsyncSharedContext) -> Self {
             let mut checkpoint = FIRST_SETUP_CHECKPOINT.lock().await;
             *checkpoint = Some(SystemTime::now());
@@ -98,15 +98,15 @@ mod asyncc {
             Self {}
         }
 
-        async fn teardown(mut self, _shared_context: AsyncSharedContext) {
+        async fn teardown(self, _shared_context: AsyncSharedContext) {
             let mut checkpoint = FIRST_TEARDOWN_CHECKPOINT.lock().await;
             *checkpoint = Some(SystemTime::now());
         }
     }
 
     pub struct SecondContext;
-    #[async_trait]
-    impl AsyncContext<'_> for SecondContext {
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for SecondContext {
         async fn setup(_shared_context: AsyncSharedContext) -> Self {
             let mut checkpoint = SECOND_SETUP_CHECKPOINT.lock().await;
             *checkpoint = Some(SystemTime::now());
@@ -116,7 +116,7 @@ mod asyncc {
             Self {}
         }
 
-        async fn teardown(mut self, _shared_context: AsyncSharedContext) {
+        async fn teardown(self, _shared_context: AsyncSharedContext) {
             let mut checkpoint = SECOND_TEARDOWN_CHECKPOINT.lock().await;
             *checkpoint = Some(SystemTime::now());
         }
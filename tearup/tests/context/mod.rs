@@ -1,2 +1,6 @@
+#[cfg(feature = "async")]
+mod adapter;
 mod combinator;
+#[cfg(feature = "proptest")]
+mod proptest;
 mod simple;
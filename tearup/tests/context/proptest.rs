@@ -0,0 +1,81 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tearup::{tearup_proptest, Context, PropFailure, SharedContext};
+
+use crate::helper::{FirstFullContext, FirstProof, SecondProof};
+
+static SETUPS: AtomicUsize = AtomicUsize::new(0);
+static RESETS: AtomicUsize = AtomicUsize::new(0);
+static CASES: AtomicUsize = AtomicUsize::new(0);
+
+#[tearup_proptest(CountingContext, cases = 10, reset = reset_counting_context)]
+fn it_setups_once_and_resets_between_cases(counter: Counter, #[strategy(0..100u32)] n: u32) {
+    assert!(n < 100);
+    // Taken from the shared context after the reset
+    assert_eq!(counter.0 as usize, RESETS.load(Ordering::SeqCst));
+
+    let cases = CASES.fetch_add(1, Ordering::SeqCst) + 1;
+    assert_eq!(SETUPS.load(Ordering::SeqCst), 1);
+    assert_eq!(RESETS.load(Ordering::SeqCst), cases - 1);
+}
+
+#[tearup_proptest(FirstFullContext)]
+fn it_mixes_fixtures_and_generated_values(
+    a: FirstProof,
+    #[strategy("[a-z]{1,10}")] s: String,
+    b: SecondProof,
+    #[strategy(0..10usize)] n: usize,
+) {
+    assert_eq!(a.0, "first_proof");
+    assert_eq!(b.0, "second_proof");
+    assert!(!s.is_empty() && n < 10);
+}
+
+#[tearup_proptest(FailureContext, cases = 50)]
+#[should_panic(expected = "minimal failing input: (\n    10,\n)")]
+fn it_reports_the_minimal_failing_case(
+    _counter: Counter,
+    _opaque: Opaque,
+    #[strategy(0..1000u32)] n: u32,
+) {
+    assert!(n < 10);
+}
+
+#[derive(Clone, Debug)]
+pub struct Counter(u32);
+
+#[derive(Clone)]
+pub struct Opaque;
+
+struct CountingContext;
+impl Context for CountingContext {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        SETUPS.fetch_add(1, Ordering::SeqCst);
+        shared_context.register(Counter(0));
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+}
+
+fn reset_counting_context(_context: &mut CountingContext, shared_context: &mut SharedContext) {
+    let resets = RESETS.fetch_add(1, Ordering::SeqCst) + 1;
+    shared_context.register(Counter(resets as u32));
+}
+
+struct FailureContext;
+impl Context for FailureContext {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        shared_context.register(Counter(0));
+        shared_context.register(Opaque);
+        Self {}
+    }
+
+    fn teardown(self, shared_context: &mut SharedContext) {
+        let failure = shared_context.get::<PropFailure>().unwrap();
+        assert_eq!(failure.context, "FailureContext");
+        assert!(failure
+            .fixtures
+            .starts_with("_counter = Counter(0), _opaque = <"));
+        assert!(failure.fixtures.ends_with("proptest::Opaque>"));
+    }
+}
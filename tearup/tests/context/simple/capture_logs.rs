@@ -0,0 +1,47 @@
+use tearup::{tearup_test, Context, SharedContext};
+
+#[tearup_test(LoggingContext, capture_logs = true)]
+fn it_captures_the_logs_of_a_passing_test() {
+    tracing::info!("not printed");
+}
+
+#[tearup_test(LoggingContext, capture_logs = true)]
+#[should_panic(expected = "printed with the logs")]
+fn it_prints_the_logs_of_a_failed_test() {
+    panic!("printed with the logs")
+}
+
+struct LoggingContext;
+impl Context for LoggingContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        tracing::info!("server started");
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {
+        tracing::info!("server stopped");
+    }
+}
+
+#[cfg(feature = "async")]
+mod asyncc {
+    use tearup::{tearup_test, AsyncContext, AsyncSharedContext};
+
+    #[tearup_test(LoggingContext, capture_logs = true)]
+    async fn it_captures_the_logs_of_a_passing_test() {
+        tracing::info!("not printed");
+    }
+
+    struct LoggingContext;
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for LoggingContext {
+        async fn setup(_shared_context: AsyncSharedContext) -> Self {
+            tracing::info!("server started");
+            Self {}
+        }
+
+        async fn teardown(self, _shared_context: AsyncSharedContext) {
+            tracing::info!("server stopped");
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::panic::catch_unwind;
+use tearup::{tearup, tearup_test, Context, SharedContext};
+
+#[tearup_test(EnvCheckContext, env = [("TEARUP_IT_ENV", "attr"), ("TEARUP_IT_PORT", 8080)], cwd = "tests")]
+fn it_sets_the_environment_around_the_context() {
+    assert_eq!(std::env::var("TEARUP_IT_ENV").unwrap(), "attr");
+    assert_eq!(std::env::var("TEARUP_IT_PORT").unwrap(), "8080");
+    assert!(std::env::current_dir().unwrap().ends_with("tests"));
+}
+
+#[test]
+fn it_restores_the_environment_after_a_panic() {
+    assert!(catch_unwind(failing).is_err());
+
+    assert_eq!(std::env::var_os("TEARUP_IT_FAILING"), None);
+}
+
+#[tearup(EnvCheckContext, env = [("TEARUP_IT_ENV", "attr"), ("TEARUP_IT_FAILING", "set")])]
+fn failing() {
+    panic!("boom")
+}
+
+struct EnvCheckContext;
+impl Context for EnvCheckContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        assert_eq!(std::env::var("TEARUP_IT_ENV").unwrap(), "attr");
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {
+        assert_eq!(std::env::var("TEARUP_IT_ENV").unwrap(), "attr");
+    }
+}
+
+#[cfg(feature = "async")]
+mod asyncc {
+    use std::panic::AssertUnwindSafe;
+    use tearup::{tearup, tearup_test, AsyncContext, AsyncSharedContext, FutureExt};
+
+    #[tearup_test(EnvCheckContext, env = [("TEARUP_IT_ASYNC_ENV", "attr"), ("TEARUP_IT_ASYNC_REMOVED", None)])]
+    async fn it_sets_the_environment_around_the_context() {
+        assert_eq!(std::env::var("TEARUP_IT_ASYNC_ENV").unwrap(), "attr");
+        assert_eq!(std::env::var_os("TEARUP_IT_ASYNC_REMOVED"), None);
+    }
+
+    #[tokio::test]
+    async fn it_restores_the_environment_after_a_panic() {
+        assert!(AssertUnwindSafe(failing()).catch_unwind().await.is_err());
+
+        assert_eq!(std::env::var_os("TEARUP_IT_ASYNC_FAILING"), None);
+    }
+
+    #[tearup(EnvCheckContext, env = [("TEARUP_IT_ASYNC_ENV", "attr"), ("TEARUP_IT_ASYNC_FAILING", "set")])]
+    async fn failing() {
+        panic!("boom")
+    }
+
+    struct EnvCheckContext;
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for EnvCheckContext {
+        async fn setup(_shared_context: AsyncSharedContext) -> Self {
+            assert_eq!(std::env::var("TEARUP_IT_ASYNC_ENV").unwrap(), "attr");
+            Self {}
+        }
+
+        async fn teardown(self, _shared_context: AsyncSharedContext) {
+            assert_eq!(std::env::var("TEARUP_IT_ASYNC_ENV").unwrap(), "attr");
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::{process::Command, thread::sleep, time::Duration};
+use tearup::{tearup_test, Context, SharedContext, TestOutcome};
+
+/// Where the child writes the outcome given to the teardown.
+const MARKER: &str = "TEARUP_INTERRUPT_MARKER";
+
+#[test]
+fn it_tears_down_on_sigterm() {
+    let marker = std::env::temp_dir().join(format!("tearup-interrupt-{}", std::process::id()));
+    let _ = std::fs::remove_file(&marker);
+
+    let status = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "context::simple::interrupt::it_is_interrupted",
+            "--exact",
+            "--nocapture",
+        ])
+        .env(MARKER, &marker)
+        .status()
+        .unwrap();
+
+    assert_eq!(status.code(), Some(128 + 15));
+    let outcome = std::fs::read_to_string(&marker).unwrap();
+    assert_eq!(outcome, "interrupted by SIGTERM");
+    std::fs::remove_file(marker).unwrap();
+}
+
+#[tearup_test(MarkerContext)]
+fn it_is_interrupted() {
+    // Only in the child of `it_tears_down_on_sigterm`
+    if std::env::var_os(MARKER).is_some() {
+        Command::new("kill")
+            .args(["-TERM", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        sleep(Duration::from_secs(10));
+        unreachable!("the process exits before")
+    }
+}
+
+struct MarkerContext;
+impl Context for MarkerContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self {}
+    }
+
+    // Run by the signal thread, the instance is handed over during the test
+    fn teardown(self, shared_context: &mut SharedContext) {
+        let outcome = shared_context.get::<TestOutcome>();
+        if let (Some(marker), Some(TestOutcome::Failed(reason))) =
+            (std::env::var_os(MARKER), outcome)
+        {
+            std::fs::write(marker, reason).unwrap();
+        }
+    }
+}
+
+/// Not `Send`: stays with the test, only its `fallback_teardown` would run on SIGINT or SIGTERM.
+#[tearup_test(LocalContext)]
+fn it_keeps_the_contexts_not_send() {}
+
+struct LocalContext(std::rc::Rc<()>);
+impl Context for LocalContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self(std::rc::Rc::new(()))
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {
+        assert_eq!(std::rc::Rc::strong_count(&self.0), 1);
+    }
+}
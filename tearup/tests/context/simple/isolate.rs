@@ -0,0 +1,89 @@
+use tearup::{tearup_test, Context, SharedContext, TestOutcome};
+
+#[derive(Clone)]
+struct SetupPid(u32);
+
+#[tearup_test(PidContext, isolate = "process")]
+fn it_runs_in_its_own_process(setup_pid: SetupPid) {
+    assert_eq!(setup_pid.0, std::process::id());
+    assert!(std::env::var("TEARUP_ISOLATED_TEST").is_ok());
+}
+
+#[tearup_test(PidContext, isolate = "process")]
+#[should_panic(expected = "failed in its process")]
+fn it_relays_the_failure() {
+    panic!("boom")
+}
+
+#[tearup_test(PidContext, isolate = "process")]
+#[should_panic(expected = "boom")]
+fn it_relays_the_panic_message() {
+    panic!("boom")
+}
+
+#[tearup_test(PidContext, isolate = "process")]
+#[should_panic]
+fn it_relays_any_panic() {
+    panic!("bam")
+}
+
+#[tearup_test(PidContext, isolate = "process")]
+#[should_panic(
+    expected = "fallback teardown: context::simple::isolate::it_survives_the_exit_of_the_test died before its teardown"
+)]
+fn it_survives_the_exit_of_the_test() {
+    std::process::exit(0)
+}
+
+struct PidContext;
+impl Context for PidContext {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        shared_context.register(SetupPid(std::process::id()));
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+
+    fn fallback_teardown(outcome: &TestOutcome) -> bool {
+        if let TestOutcome::Failed(reason) = outcome {
+            panic!("fallback teardown: {}", reason)
+        }
+        true
+    }
+}
+
+#[cfg(feature = "async")]
+mod asyncc {
+    use tearup::{tearup_test, AsyncContext, AsyncSharedContext};
+
+    use super::SetupPid;
+
+    #[tearup_test(PidContext, isolate = "process")]
+    async fn it_runs_in_its_own_process(setup_pid: SetupPid) {
+        assert_eq!(setup_pid.0, std::process::id());
+        assert!(std::env::var("TEARUP_ISOLATED_TEST").is_ok());
+    }
+
+    #[tearup_test(PidContext, isolate = "process")]
+    #[should_panic(expected = "multi\nline")]
+    async fn it_relays_the_panic_message() {
+        panic!("multi\nline")
+    }
+
+    #[tearup_test(PidContext, isolate = "process")]
+    #[should_panic(expected = "died before its teardown: killed by signal")]
+    async fn it_survives_the_abort_of_the_test() {
+        std::process::abort()
+    }
+
+    struct PidContext;
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for PidContext {
+        async fn setup(shared_context: AsyncSharedContext) -> Self {
+            shared_context.register(SetupPid(std::process::id())).await;
+            Self {}
+        }
+
+        async fn teardown(self, _shared_context: AsyncSharedContext) {}
+    }
+}
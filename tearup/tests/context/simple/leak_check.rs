@@ -0,0 +1,73 @@
+use std::{fs::File, sync::Mutex};
+use tearup::{tearup_test, Context, LeakCheck, SharedContext};
+
+/// Absolute, the working directory is moved by the `cwd` tests.
+const MANIFEST: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+
+/// Opened by the setup, closed or not by the teardown.
+static FILE: Mutex<Option<File>> = Mutex::new(None);
+
+#[tearup_test(LeakyContext)]
+#[should_panic(expected = "LeakyContext leaked after its teardown:\n  fd ")]
+fn it_fails_on_a_file_left_open() {}
+
+struct LeakyContext;
+impl Context for LeakyContext {
+    const LEAK_CHECK: Option<LeakCheck> = Some(LeakCheck::Fail);
+
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        *FILE.lock().unwrap() = Some(File::open(MANIFEST).unwrap());
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+}
+
+#[cfg(feature = "async")]
+mod asyncc {
+    use std::{fs::File, sync::Mutex};
+    use tearup::{tearup_test, AsyncContext, AsyncSharedContext, LeakCheck};
+
+    static FILE: Mutex<Option<File>> = Mutex::new(None);
+
+    #[tearup_test(LeakyContext)]
+    #[should_panic(expected = "LeakyContext leaked after its teardown:\n  fd ")]
+    async fn it_fails_on_a_file_left_open() {}
+
+    struct LeakyContext;
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for LeakyContext {
+        const LEAK_CHECK: Option<LeakCheck> = Some(LeakCheck::Fail);
+
+        async fn setup(_shared_context: AsyncSharedContext) -> Self {
+            *FILE.lock().unwrap() = Some(File::open(super::MANIFEST).unwrap());
+            Self {}
+        }
+
+        async fn teardown(self, _shared_context: AsyncSharedContext) {}
+    }
+}
+
+/// The reactors of async-std and smol open their file descriptors on first use, in the first test.
+#[cfg(all(feature = "tokio", not(any(feature = "async-std", feature = "smol"))))]
+mod adapter {
+    use tearup::{tearup_test, Async, Context, LeakCheck, SharedContext};
+
+    type AsyncTidyContext = Async<TidyContext>;
+
+    /// The blocking pool threads started by the adapter are not leaks,
+    /// in its own process to leave out the other tests.
+    #[tearup_test(AsyncTidyContext, isolate = "process")]
+    async fn it_passes_through_the_adapter() {}
+
+    struct TidyContext;
+    impl Context for TidyContext {
+        const LEAK_CHECK: Option<LeakCheck> = Some(LeakCheck::Fail);
+
+        fn setup(_shared_context: &mut SharedContext) -> Self {
+            Self {}
+        }
+
+        fn teardown(self, _shared_context: &mut SharedContext) {}
+    }
+}
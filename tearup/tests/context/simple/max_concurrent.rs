@@ -0,0 +1,131 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread::sleep,
+    time::Duration,
+};
+use tearup::{tearup, tearup_test, ConcurrencyWaits, Context, ContextCombinator, SharedContext};
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+#[tearup_test(HeavyContext)]
+fn it_limits_the_live_contexts(waits: ConcurrencyWaits) {
+    assert_eq!(waits.0.len(), 1);
+    assert!(waits.0[0].context.ends_with("HeavyContext"));
+    sleep(Duration::from_millis(20));
+}
+
+#[tearup_test(HeavyContext)]
+fn it_limits_the_live_contexts_again(waits: ConcurrencyWaits) {
+    assert_eq!(waits.0.len(), 1);
+    sleep(Duration::from_millis(20));
+}
+
+#[tearup_test(HeavyContext)]
+fn it_limits_the_live_contexts_once_more() {
+    sleep(Duration::from_millis(20));
+}
+
+struct HeavyContext;
+impl Context for HeavyContext {
+    const MAX_CONCURRENT: usize = 1;
+
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        assert_eq!(LIVE.fetch_add(1, Ordering::SeqCst), 0);
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {
+        LIVE.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Getting the missing fixture panics after the setup, before the teardown.
+#[test]
+fn it_releases_the_slot_of_a_test_panicking_before_its_teardown() {
+    let missing = std::panic::catch_unwind(it_misses_a_fixture);
+    assert!(missing.is_err());
+
+    it_gets_the_slot_back();
+}
+
+#[tearup(SingleContext)]
+fn it_misses_a_fixture(_missing: NeverRegistered) {}
+
+#[derive(Clone)]
+struct NeverRegistered;
+
+#[tearup(SingleContext)]
+fn it_gets_the_slot_back() {}
+
+struct SingleContext;
+impl Context for SingleContext {
+    const MAX_CONCURRENT: usize = 1;
+
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+}
+
+type TwiceContext = ContextCombinator<OnceContext, OnceContext>;
+
+#[tearup_test(TwiceContext)]
+#[should_panic(
+    expected = "OnceContext is set up more than its MAX_CONCURRENT of 1 times by the same test"
+)]
+fn it_panics_instead_of_waiting_for_itself() {}
+
+struct OnceContext;
+impl Context for OnceContext {
+    const MAX_CONCURRENT: usize = 1;
+
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+}
+
+#[cfg(feature = "tokio")]
+mod asyncc {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+    use tearup::{tearup_test, AsyncContext, AsyncSharedContext, ConcurrencyWaits};
+    use tokio::time::sleep;
+
+    static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+    #[tearup_test(HeavyContext)]
+    async fn it_limits_the_live_contexts(waits: ConcurrencyWaits) {
+        assert_eq!(waits.0.len(), 1);
+        sleep(Duration::from_millis(20)).await;
+    }
+
+    #[tearup_test(HeavyContext)]
+    async fn it_limits_the_live_contexts_again() {
+        sleep(Duration::from_millis(20)).await;
+    }
+
+    #[tearup_test(HeavyContext)]
+    async fn it_limits_the_live_contexts_once_more() {
+        sleep(Duration::from_millis(20)).await;
+    }
+
+    struct HeavyContext;
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for HeavyContext {
+        const MAX_CONCURRENT: usize = 2;
+
+        async fn setup(_shared_context: AsyncSharedContext) -> Self {
+            assert!(LIVE.fetch_add(1, Ordering::SeqCst) < 2);
+            Self {}
+        }
+
+        async fn teardown(self, _shared_context: AsyncSharedContext) {
+            LIVE.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
@@ -1,2 +1,19 @@
+#[cfg(feature = "tracing")]
+mod capture_logs;
+mod env;
+#[cfg(unix)]
+mod interrupt;
+mod isolate;
+#[cfg(target_os = "linux")]
+mod leak_check;
+mod max_concurrent;
+mod observer;
+mod outcome;
+#[cfg(feature = "async")]
+mod runtime;
+mod serial;
 mod setup_teardown;
+mod test_attr;
+#[cfg(unix)]
+mod timings;
 mod with_arguments;
@@ -0,0 +1,146 @@
+use lazy_static::lazy_static;
+use std::sync::{Arc, Mutex};
+use tearup::{
+    tearup_test, Context, ContextCombinator, SharedContext, TearupEvent, TearupEventKind,
+    TearupObserver, TestName, TestOutcome,
+};
+
+lazy_static! {
+    static ref RECORDER: Arc<Recorder> = Arc::new(Recorder::default());
+}
+
+#[derive(Default)]
+struct Recorder(Mutex<Vec<TearupEvent>>);
+impl TearupObserver for Recorder {
+    fn on_event(&self, event: &TearupEvent) {
+        self.0.lock().unwrap().push(event.clone());
+    }
+}
+
+/// The events of the test so far, as `(context without its path, kind without its fields)`.
+fn events_of(test: &str) -> Vec<(&'static str, String)> {
+    RECORDER
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|event| matches!(event.test, Some(name) if name.ends_with(test)))
+        .map(|event| {
+            let path = event.context.split('<').next().unwrap();
+            let context = path.rsplit("::").next().unwrap();
+            let kind = format!("{:?}", event.kind);
+            (context, kind.split([' ', '(']).next().unwrap().to_owned())
+        })
+        .collect()
+}
+
+fn event(context: &'static str, kind: &str) -> (&'static str, String) {
+    (context, kind.to_owned())
+}
+
+#[tearup_test(ObservedContext)]
+fn it_sends_the_events_of_the_lifecycle() {
+    assert_eq!(
+        events_of("observer::it_sends_the_events_of_the_lifecycle"),
+        vec![
+            event("ObservedContext", "SetupStarted"),
+            event("ObservedContext", "SetupFinished"),
+            event("ObservedContext", "TestStarted"),
+        ]
+    );
+}
+
+type Both = ContextCombinator<ObservedContext, OtherContext>;
+#[tearup_test(Both)]
+fn it_sends_the_events_of_the_members() {
+    assert_eq!(
+        events_of("observer::it_sends_the_events_of_the_members"),
+        vec![
+            event("ContextCombinator", "SetupStarted"),
+            event("ObservedContext", "SetupStarted"),
+            event("ObservedContext", "SetupFinished"),
+            event("OtherContext", "SetupStarted"),
+            event("OtherContext", "SetupFinished"),
+            event("ContextCombinator", "SetupFinished"),
+            event("ContextCombinator", "TestStarted"),
+        ]
+    );
+}
+
+struct ObservedContext;
+impl Context for ObservedContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self {}
+    }
+
+    fn teardown(self, shared_context: &mut SharedContext) {
+        let test = shared_context.get::<TestName>().unwrap();
+        assert_eq!(
+            events_of(test.0).last(),
+            Some(&event("ObservedContext", "TeardownStarted"))
+        );
+
+        let finished = RECORDER.0.lock().unwrap().iter().any(|event| {
+            event.test == Some(test.0)
+                && matches!(
+                    event.kind,
+                    TearupEventKind::TestFinished {
+                        outcome: TestOutcome::Passed,
+                        ..
+                    }
+                )
+        });
+        assert!(finished);
+    }
+
+    fn observers() -> Vec<Arc<dyn TearupObserver>> {
+        vec![RECORDER.clone()]
+    }
+}
+
+struct OtherContext;
+impl Context for OtherContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+
+    fn observers() -> Vec<Arc<dyn TearupObserver>> {
+        vec![RECORDER.clone()]
+    }
+}
+
+#[cfg(feature = "async")]
+mod asyncc {
+    use std::sync::Arc;
+    use tearup::{tearup_test, AsyncContext, AsyncSharedContext, TearupObserver};
+
+    use super::{event, events_of, RECORDER};
+
+    #[tearup_test(ObservedContext)]
+    async fn it_sends_the_events_of_the_lifecycle() {
+        assert_eq!(
+            events_of("asyncc::it_sends_the_events_of_the_lifecycle"),
+            vec![
+                event("ObservedContext", "SetupStarted"),
+                event("ObservedContext", "SetupFinished"),
+                event("ObservedContext", "TestStarted"),
+            ]
+        );
+    }
+
+    struct ObservedContext;
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for ObservedContext {
+        async fn setup(_shared_context: AsyncSharedContext) -> Self {
+            Self {}
+        }
+
+        async fn teardown(self, _shared_context: AsyncSharedContext) {}
+
+        fn observers() -> Vec<Arc<dyn TearupObserver>> {
+            vec![RECORDER.clone()]
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use lazy_static::lazy_static;
+use std::{panic::catch_unwind, sync::Mutex};
+use tearup::{tearup, Context, SharedContext, TestOutcome};
+
+lazy_static! {
+    static ref OUTCOMES: Mutex<Vec<TestOutcome>> = Mutex::new(vec![]);
+}
+
+#[test]
+fn it_gives_the_outcome_to_the_teardown() {
+    passing();
+    assert!(catch_unwind(failing).is_err());
+
+    assert_eq!(
+        *OUTCOMES.lock().unwrap(),
+        vec![TestOutcome::Passed, TestOutcome::Failed("boom".to_owned())]
+    );
+}
+
+struct OutcomeContext;
+impl Context for OutcomeContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self {}
+    }
+
+    fn teardown(self, shared_context: &mut SharedContext) {
+        let outcome = shared_context.get::<TestOutcome>().unwrap();
+        OUTCOMES.lock().unwrap().push(outcome);
+    }
+}
+
+#[tearup(OutcomeContext)]
+fn passing() {}
+
+#[tearup(OutcomeContext)]
+fn failing() {
+    panic!("boom")
+}
+
+#[cfg(feature = "async")]
+mod asyncc {
+    use lazy_static::lazy_static;
+    use std::panic::AssertUnwindSafe;
+    use tearup::{tearup, AsyncContext, AsyncSharedContext, FutureExt, TestOutcome};
+    use tokio::sync::Mutex;
+
+    lazy_static! {
+        static ref OUTCOMES: Mutex<Vec<TestOutcome>> = Mutex::new(vec![]);
+    }
+
+    #[tokio::test]
+    async fn it_gives_the_outcome_to_the_teardown() {
+        passing().await;
+        assert!(AssertUnwindSafe(failing()).catch_unwind().await.is_err());
+
+        assert_eq!(
+            *OUTCOMES.lock().await,
+            vec![TestOutcome::Passed, TestOutcome::Failed("boom".to_owned())]
+        );
+    }
+
+    struct OutcomeContext;
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for OutcomeContext {
+        async fn setup(_shared_context: AsyncSharedContext) -> Self {
+            Self {}
+        }
+
+        async fn teardown(self, mut shared_context: AsyncSharedContext) {
+            let outcome = shared_context.get::<TestOutcome>().await.unwrap();
+            OUTCOMES.lock().await.push(outcome);
+        }
+    }
+
+    #[tearup(OutcomeContext)]
+    async fn passing() {}
+
+    #[tearup(OutcomeContext)]
+    async fn failing() {
+        panic!("boom")
+    }
+}
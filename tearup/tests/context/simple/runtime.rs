@@ -0,0 +1,68 @@
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+use tearup::tearup_test;
+
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+use crate::helper::{AsyncFirstFullContext, FirstProof};
+
+#[cfg(feature = "tokio")]
+#[tearup_test(AsyncFirstFullContext, flavor = "multi_thread", worker_threads = 2)]
+async fn it_forwards_the_tokio_options(a: FirstProof) {
+    assert_eq!(a.0, "first_proof");
+    // panics on a current_thread runtime
+    tokio::task::block_in_place(|| {});
+}
+
+#[cfg(feature = "tokio")]
+#[tearup_test(AsyncFirstFullContext, start_paused = true)]
+async fn it_starts_paused(a: FirstProof) {
+    assert_eq!(a.0, "first_proof");
+
+    let before = tokio::time::Instant::now();
+    tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+    assert!(before.elapsed() >= std::time::Duration::from_secs(3600));
+}
+
+#[cfg(feature = "async-std")]
+#[tearup_test(AsyncFirstFullContext, runtime = "async_std")]
+async fn it_runs_on_async_std(a: FirstProof) {
+    assert_eq!(a.0, "first_proof");
+    async_std::task::yield_now().await;
+}
+
+#[cfg(feature = "smol")]
+mod on_smol {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tearup::{tearup_test, AsyncContext, AsyncSharedContext};
+
+    use crate::helper::FirstProof;
+
+    static SMOL_TEARDOWN: AtomicBool = AtomicBool::new(false);
+
+    #[test]
+    fn it_runs_on_smol_then_teardown() {
+        it_runs_on_smol();
+
+        assert!(SMOL_TEARDOWN.load(Ordering::SeqCst));
+    }
+
+    #[tearup_test(SmolContext, runtime = "smol")]
+    async fn it_runs_on_smol(a: FirstProof) {
+        assert_eq!(a.0, "first_proof");
+        smol::Timer::after(std::time::Duration::from_millis(1)).await;
+    }
+
+    struct SmolContext;
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for SmolContext {
+        async fn setup(shared_context: AsyncSharedContext) -> Self {
+            shared_context
+                .register(FirstProof("first_proof".to_owned()))
+                .await;
+            Self {}
+        }
+
+        async fn teardown(self, _shared_context: AsyncSharedContext) {
+            SMOL_TEARDOWN.store(true, Ordering::SeqCst);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread::sleep,
+    time::Duration,
+};
+use tearup::{tearup_test, Context, Serial, SharedContext};
+
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+fn run_alone() {
+    assert_eq!(RUNNING.fetch_add(1, Ordering::SeqCst), 0);
+    sleep(Duration::from_millis(50));
+    RUNNING.fetch_sub(1, Ordering::SeqCst);
+}
+
+#[tearup_test(NoGroupContext, serial = "it-serial")]
+fn it_runs_alone_in_its_group() {
+    run_alone();
+}
+
+#[tearup_test(NoGroupContext, serial = "it-serial", cross_process = true)]
+fn it_runs_alone_in_its_group_across_processes() {
+    run_alone();
+}
+
+#[tearup_test(GroupContext)]
+fn it_runs_alone_in_the_group_of_its_context() {
+    run_alone();
+}
+
+struct NoGroupContext;
+impl Context for NoGroupContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        run_alone();
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {
+        run_alone();
+    }
+}
+
+struct GroupContext;
+impl Context for GroupContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+
+    fn serial() -> Vec<Serial> {
+        vec![Serial::group("it-serial")]
+    }
+}
+
+#[cfg(feature = "async")]
+mod asyncc {
+    use tearup::{tearup_test, AsyncContext, AsyncSharedContext, Serial};
+
+    use super::run_alone;
+
+    #[tearup_test(GroupContext)]
+    async fn it_runs_alone_in_the_group_of_its_context() {
+        run_alone();
+    }
+
+    #[tearup_test(GroupContext, serial = "it-serial-async")]
+    async fn it_runs_alone_in_all_its_groups() {
+        run_alone();
+    }
+
+    struct GroupContext;
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for GroupContext {
+        async fn setup(_shared_context: AsyncSharedContext) -> Self {
+            run_alone();
+            Self {}
+        }
+
+        async fn teardown(self, _shared_context: AsyncSharedContext) {}
+
+        fn serial() -> Vec<Serial> {
+            vec![Serial::group("it-serial")]
+        }
+    }
+}
@@ -41,7 +41,6 @@ fn teardown_panic() {}
 
 #[cfg(feature = "async")]
 mod asyncc {
-    use async_trait::async_trait;
     use lazy_static::lazy_static;
     use std::time::{Duration, SystemTime};
     use tearup::{tearup, AsyncContext, AsyncSharedContext};
@@ -62,8 +61,8 @@ mod asyncc {
     }
 
     struct NiceContext;
-    #[async_trait]
-    impl AsyncContext<'_> for NiceContext {
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for NiceContext {
         async fn setup(_shared_context: AsyncSharedContext) -> Self {
             let mut checkpoint = SETUP_CHECKPOINT.lock().await;
             *checkpoint = Some(SystemTime::now());
@@ -73,7 +72,7 @@ mod asyncc {
             Self {}
         }
 
-        async fn teardown(mut self, _shared_context: AsyncSharedContext) {
+        async fn teardown(self, _shared_context: AsyncSharedContext) {
             let mut checkpoint = TEARDOWN_CHECKPOINT.lock().await;
             *checkpoint = Some(SystemTime::now());
         }
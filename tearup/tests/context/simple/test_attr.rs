@@ -0,0 +1,43 @@
+use tearup::tearup_test;
+
+use crate::helper::{FirstFullContext, FirstProof, SecondProof};
+
+#[tearup_test(FirstFullContext, test_attr = test_case::test_case(4))]
+fn it_replaces_the_test_attribute(#[passthrough] n: u32, a: FirstProof) {
+    assert_eq!(n, 4);
+    assert_eq!(a.0, "first_proof");
+}
+
+#[tearup_test(FirstFullContext)]
+#[test]
+fn it_keeps_an_existing_test_attribute(b: SecondProof) {
+    assert_eq!(b.0, "second_proof");
+}
+
+#[tearup_test(FirstFullContext)]
+#[test_case::test_case(4)]
+fn it_keeps_an_existing_test_case(#[passthrough] n: u32, b: SecondProof) {
+    assert_eq!(n, 4);
+    assert_eq!(b.0, "second_proof");
+}
+
+#[cfg(feature = "async")]
+mod asyncc {
+    use tearup::tearup_test;
+
+    use crate::helper::{AsyncFirstFullContext, FirstProof};
+
+    #[tearup_test(AsyncFirstFullContext, test_attr = tokio::test(flavor = "multi_thread"))]
+    async fn it_replaces_the_async_test_attribute(a: FirstProof) {
+        assert_eq!(a.0, "first_proof");
+        // panics on a current_thread runtime
+        tokio::task::block_in_place(|| {});
+    }
+
+    #[tearup_test(AsyncFirstFullContext, test_attr = test_case::test_case(4))]
+    #[tokio::test]
+    async fn it_passes_through_to_an_async_test_attribute(#[passthrough] n: u32, a: FirstProof) {
+        assert_eq!(n, 4);
+        assert_eq!(a.0, "first_proof");
+    }
+}
@@ -0,0 +1,60 @@
+use std::{process::Command, thread::sleep, time::Duration};
+use tearup::{tearup_test, Context, ContextCombinator, SharedContext};
+
+#[test]
+fn it_reports_the_timings_at_exit() {
+    let target_dir = std::env::temp_dir().join(format!("tearup-timings-{}", std::process::id()));
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "context::simple::timings::it_is_timed",
+            "--exact",
+            "--nocapture",
+        ])
+        .env("TEARUP_TIMINGS", "1")
+        .env("TEARUP_SLOW_SETUP", "0.01")
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("SlowContext took "), "{}", stderr);
+    assert!(stderr.contains(" to set up for "), "{}", stderr);
+    assert!(stderr.contains("\ncontext "), "{}", stderr);
+
+    let report = std::fs::read_dir(target_dir.join("tearup"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.to_string_lossy().ends_with(".json"))
+        .unwrap();
+    let json = std::fs::read_to_string(report).unwrap();
+    assert!(json.starts_with("{\"slow_setup_ms\":10,"), "{}", json);
+    assert!(json.contains("::SlowContext\",\"setups\":1,"), "{}", json);
+    assert!(json.contains("::FastContext\"]}"), "{}", json);
+    std::fs::remove_dir_all(target_dir).unwrap();
+}
+
+type Both = ContextCombinator<SlowContext, FastContext>;
+
+#[tearup_test(Both)]
+fn it_is_timed() {}
+
+struct SlowContext;
+impl Context for SlowContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        sleep(Duration::from_millis(20));
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+}
+
+struct FastContext;
+impl Context for FastContext {
+    fn setup(_shared_context: &mut SharedContext) -> Self {
+        Self {}
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {}
+}
@@ -41,13 +41,13 @@ impl Context for SecondFullContext {
 
 #[cfg(feature = "async")]
 pub mod asyncc {
-    use tearup::{async_trait, AsyncContext, AsyncSharedContext};
+    use tearup::{AsyncContext, AsyncSharedContext};
 
     use crate::helper::{FirstProof, FourthProof, SecondProof, ThirdProof};
 
     pub struct AsyncFirstFullContext;
-    #[async_trait]
-    impl AsyncContext<'_> for AsyncFirstFullContext {
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for AsyncFirstFullContext {
         async fn setup(shared_context: AsyncSharedContext) -> Self {
             shared_context
                 .register(FirstProof("first_proof".to_owned()))
@@ -62,8 +62,8 @@ pub mod asyncc {
     }
 
     pub struct AsyncSecondFullContext;
-    #[async_trait]
-    impl AsyncContext<'_> for AsyncSecondFullContext {
+    #[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+    impl AsyncContext for AsyncSecondFullContext {
         async fn setup(mut shared_context: AsyncSharedContext) -> Self {
             let first = shared_context.get::<FirstProof>().await.unwrap().0;
             shared_context
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use criterion::{criterion_group, criterion_main, Criterion};
+use tearup::{
+    bench::{with_async_context, with_context},
+    AsyncContext, AsyncSharedContext, Context, SharedContext,
+};
+
+fn sync_db(c: &mut Criterion) {
+    // The same context as in the tests, setup once outside of the measured section
+    with_context::<DbContext, _>(c, "sync_query", |fixtures, b| {
+        let mut db = fixtures.get::<DbClient>().unwrap();
+        b.iter(|| db.query("SELECT 1"))
+    });
+}
+
+fn async_db(c: &mut Criterion) {
+    with_async_context::<AsyncDbContext, _>(c, "async_query", |fixtures, b| {
+        let db = fixtures.get::<DbClient>().unwrap();
+        b.to_async(fixtures.runtime()).iter(|| {
+            let mut db = db.clone();
+            async move { db.async_query("SELECT 1").await }
+        })
+    });
+}
+
+criterion_group!(benches, sync_db, async_db);
+criterion_main!(benches);
+
+struct DbContext;
+
+impl Context for DbContext {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        let mut db_client = DbClient::new("random_db_name");
+        db_client.create_db();
+        shared_context.register(db_client);
+        Self {}
+    }
+
+    fn teardown(self, shared_context: &mut SharedContext) {
+        shared_context.get::<DbClient>().unwrap().drop_db();
+    }
+}
+
+struct AsyncDbContext;
+
+#[async_trait]
+impl AsyncContext for AsyncDbContext {
+    async fn setup(shared_context: AsyncSharedContext) -> Self {
+        let mut db_client = DbClient::new("random_db_name");
+        db_client.create_db();
+        shared_context.register(db_client).await;
+        Self {}
+    }
+
+    async fn teardown(mut self, mut shared_context: AsyncSharedContext) {
+        shared_context.get::<DbClient>().await.unwrap().drop_db();
+    }
+}
+
+#[derive(Clone)]
+pub struct DbClient {
+    #[allow(unused)]
+    name: String,
+}
+
+impl DbClient {
+    pub fn new(db_name: &str) -> Self {
+        DbClient {
+            name: db_name.to_string(),
+        }
+    }
+    pub fn create_db(&mut self) {}
+    pub fn drop_db(&mut self) {}
+    pub fn query(&mut self, _query: &str) -> String {
+        "some res".to_string()
+    }
+    pub async fn async_query(&mut self, _query: &str) -> String {
+        "some res".to_string()
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::{Arc, Mutex};
+use tearup::{tearup_test, Context, Pooled, PooledContext, SharedContext};
+
+// Both tests get a fresh table, the database is created once
+#[tearup_test(PooledDbContext)]
+fn it_inserts_a_row(db: DbClient) {
+    assert!(db.rows().is_empty());
+    db.insert("first");
+    assert_eq!(db.rows(), vec!["first"]);
+}
+
+#[tearup_test(PooledDbContext)]
+fn it_inserts_another_row(db: DbClient) {
+    assert!(db.rows().is_empty());
+    db.insert("second");
+    assert_eq!(db.rows(), vec!["second"]);
+}
+
+type PooledDbContext = Pooled<DbContext>;
+
+struct DbContext {
+    db: DbClient,
+}
+
+impl Context for DbContext {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        let db = DbClient::create_db();
+        shared_context.register(db.clone());
+        Self { db }
+    }
+
+    fn teardown(self, _shared_context: &mut SharedContext) {
+        self.db.drop_db();
+    }
+}
+
+impl PooledContext for DbContext {
+    const POOL_SIZE: usize = 2;
+
+    fn reset(&mut self, _shared_context: &mut SharedContext) -> Result<(), String> {
+        self.db.truncate();
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct DbClient(Arc<Mutex<Vec<String>>>);
+
+impl DbClient {
+    fn create_db() -> Self {
+        Self(Arc::default())
+    }
+
+    fn insert(&self, row: &str) {
+        self.0.lock().unwrap().push(row.to_owned());
+    }
+
+    fn rows(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn truncate(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    fn drop_db(self) {}
+}
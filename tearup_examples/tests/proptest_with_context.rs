@@ -0,0 +1,53 @@
+use tearup::{proptest::prelude::*, tearup_proptest, Context, SharedContext};
+
+// The context is setup once then each generated case runs against it
+#[tearup_proptest(DbContext, cases = 32, reset = truncate_tables)]
+fn it_stores_any_name(mut db: DbClient, #[strategy("[a-z]{1,16}")] name: String) {
+    db.execute(&format!("INSERT INTO users VALUES ('{}')", name));
+    prop_assert_eq!("some res", db.query("SELECT name FROM users"));
+}
+
+struct DbContext {}
+
+impl Context for DbContext {
+    fn setup(shared_context: &mut SharedContext) -> Self {
+        let mut db_client = DbClient::new("random_db_name");
+
+        db_client.create_db();
+
+        shared_context.register(db_client);
+
+        Self {}
+    }
+
+    fn teardown(self, shared_context: &mut SharedContext) {
+        shared_context.get::<DbClient>().unwrap().drop_db();
+    }
+}
+
+fn truncate_tables(_context: &mut DbContext, shared_context: &mut SharedContext) {
+    shared_context
+        .get::<DbClient>()
+        .unwrap()
+        .execute("TRUNCATE users");
+}
+
+#[derive(Clone)]
+pub struct DbClient {
+    #[allow(unused)]
+    name: String,
+}
+
+impl DbClient {
+    pub fn new(db_name: &str) -> Self {
+        DbClient {
+            name: db_name.to_string(),
+        }
+    }
+    pub fn create_db(&mut self) {}
+    pub fn drop_db(&mut self) {}
+    pub fn execute(&mut self, _query: &str) {}
+    pub fn query(&mut self, _query: &str) -> String {
+        "some res".to_string()
+    }
+}
@@ -1,56 +1,43 @@
 use async_trait::async_trait;
-use std::time::Duration;
+use std::{net::TcpListener, time::Duration};
 use tearup::{
-    helper::{async_ready_when, ready_when},
-    tearup_test, AsyncContext, AsyncSharedContext, Context, FutureExt, SharedContext,
+    helper::{async_ready_when_or_timeout, probe, Backoff},
+    tearup_test, AsyncContext, AsyncSharedContext, Blocking,
 };
 
 #[tearup_test(AsyncReadyWhenContext)]
 async fn setup_barely_timeout_with_ready_when() {}
 
-struct AsyncReadyWhenContext;
+// No need to write a sync version of the context for sync tests
+type SyncReadyWhenContext = Blocking<AsyncReadyWhenContext>;
 
-#[async_trait]
-impl AsyncContext<'_> for AsyncReadyWhenContext {
-    async fn setup(_shared_context: AsyncSharedContext) -> Self {
-        launch_server();
-
-        async_ready_when(
-            || async move { ping_server().await.is_ok() }.boxed(),
-            Duration::from_millis(100),
-        )
-        .await;
-
-        Self {}
-    }
-
-    async fn teardown(mut self, _shared_context: AsyncSharedContext) {}
-}
+#[tearup_test(SyncReadyWhenContext)]
+fn setup_barely_timeout_with_ready_when_in_sync_test() {}
 
-fn launch_server() {}
-
-async fn ping_server() -> Result<(), ()> {
-    Ok(())
+struct AsyncReadyWhenContext {
+    _server: TcpListener,
 }
 
-struct SyncReadyWhenContext;
-
 #[async_trait]
-impl Context for SyncReadyWhenContext {
-    fn setup(_shared_context: &mut SharedContext) -> Self {
-        launch_server();
+impl AsyncContext for AsyncReadyWhenContext {
+    async fn setup(_shared_context: AsyncSharedContext) -> Self {
+        let server = launch_server();
+        let address = server.local_addr().unwrap().to_string();
 
-        ready_when(
-            Box::new(|| sync_ping_server().is_ok()),
-            Duration::from_millis(100),
-        );
+        async_ready_when_or_timeout(
+            probe::async_probe(probe::tcp(address)),
+            Backoff::exponential(Duration::from_millis(10), Duration::from_millis(100)),
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap();
 
-        Self {}
+        Self { _server: server }
     }
 
-    fn teardown(self, _shared_context: &mut SharedContext) {}
+    async fn teardown(mut self, _shared_context: AsyncSharedContext) {}
 }
 
-fn sync_ping_server() -> Result<(), ()> {
-    Ok(())
+fn launch_server() -> TcpListener {
+    TcpListener::bind("127.0.0.1:0").unwrap()
 }
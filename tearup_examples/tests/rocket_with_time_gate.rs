@@ -1,11 +1,10 @@
-use std::{sync::Mutex, time::Duration};
+use std::time::Duration;
 
 use async_trait::async_trait;
-use lazy_static::lazy_static;
 use reqwest::StatusCode;
 use rocket::fairing::AdHoc;
 use tearup::{
-    helper::{AsyncReadyFn, AsyncTimeGate},
+    helper::{AsyncGateSender, AsyncTimeGate},
     tearup_test, AsyncContext, AsyncSharedContext,
 };
 use tearup_examples::rocket;
@@ -19,25 +18,20 @@ async fn it_launches_the_server(url: BaseUrl) {
 
 struct RocketContext {
     _srv_life: ServerLife,
-    port: u16,
 }
 
-pub type ServerLife = rocket::tokio::task::JoinHandle<rocket::Rocket<rocket::Ignite>>;
-
-lazy_static! {
-    static ref AVAILABLE_PORTS: Mutex<Vec<u16>> = Mutex::new((8001..9000).collect());
-}
+pub type ServerLife = rocket::tokio::task::JoinHandle<()>;
 
 #[async_trait]
-impl<'a> AsyncContext<'a> for RocketContext {
+impl AsyncContext for RocketContext {
     async fn setup(shared_context: AsyncSharedContext) -> Self {
-        let port = choose_port().await;
-
-        let gate = AsyncTimeGate::default();
+        let gate = AsyncTimeGate::with_payload();
 
-        let _srv_life = launch_server_then_notif_ready(port, gate.notifier()).await;
+        // Port 0 lets the OS choose, the actual port comes back through the gate
+        let _srv_life = launch_server_then_notif_ready(0, gate.sender()).await;
 
-        gate.wait_signal_or_timeout(Duration::from_millis(115))
+        let port = gate
+            .wait_signal_or_timeout(Duration::from_millis(115))
             .await
             .unwrap();
 
@@ -45,31 +39,29 @@ impl<'a> AsyncContext<'a> for RocketContext {
             .register(BaseUrl(format!("http://localhost:{}/", port)))
             .await;
 
-        Self { _srv_life, port }
+        Self { _srv_life }
     }
 
-    async fn teardown(mut self, _shared_context: AsyncSharedContext) {
-        free_port(self.port).await;
-    }
-}
-
-async fn choose_port() -> u16 {
-    AVAILABLE_PORTS.lock().unwrap().remove(0)
+    async fn teardown(self, _shared_context: AsyncSharedContext) {}
 }
 
-async fn free_port(port: u16) {
-    AVAILABLE_PORTS.lock().unwrap().push(port);
-}
-
-async fn launch_server_then_notif_ready<'a>(port: u16, ready: AsyncReadyFn<'static>) -> ServerLife {
+async fn launch_server_then_notif_ready(
+    port: u16,
+    ready: AsyncGateSender<u16, String>,
+) -> ServerLife {
     tokio::task::spawn(async move {
-        rocket(port)
-            .attach(AdHoc::on_liftoff("Liftoff notifier", |_| {
-                Box::pin(async move { ready().await })
+        let failure = ready.clone();
+        let launched = rocket(port)
+            .attach(AdHoc::on_liftoff("Liftoff notifier", move |rocket| {
+                ready.ready(rocket.config().port);
+                Box::pin(async {})
             }))
             .launch()
-            .await
-            .unwrap()
+            .await;
+
+        if let Err(err) = launched {
+            failure.fail(err.to_string());
+        }
     })
 }
 
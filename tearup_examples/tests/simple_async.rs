@@ -15,7 +15,7 @@ struct DbContext {
 }
 
 #[async_trait]
-impl<'a> AsyncContext<'a> for DbContext {
+impl AsyncContext for DbContext {
     async fn setup(shared_context: AsyncSharedContext) -> Self {
         let mut db_client = DbClient::new("random_db_name");
 
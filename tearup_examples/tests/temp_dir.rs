@@ -0,0 +1,40 @@
+use std::{fs, path::PathBuf};
+use tearup::{
+    helper::{AsyncTempDirContext, TempDir, TempDirContext, TempDirTemplate, TemplateDirContext},
+    tearup_test, Async,
+};
+
+#[tearup_test(TempDirContext)]
+fn it_writes_in_a_scratch_directory(dir: TempDir) {
+    fs::write(dir.path().join("report.txt"), "all good").unwrap();
+
+    assert!(dir.path().join("report.txt").exists());
+}
+
+#[tearup_test(AsyncTempDirContext)]
+async fn it_writes_in_a_scratch_directory_in_async_test(dir: TempDir) {
+    fs::write(dir.path().join("report.txt"), "all good").unwrap();
+
+    assert!(dir.path().join("report.txt").exists());
+}
+
+struct Sources;
+impl TempDirTemplate for Sources {
+    fn template() -> PathBuf {
+        "tests".into()
+    }
+}
+
+type SourcesDirContext = TemplateDirContext<Sources>;
+
+#[tearup_test(SourcesDirContext)]
+fn it_starts_from_a_copy_of_the_template(dir: TempDir) {
+    assert!(dir.path().join("temp_dir.rs").exists());
+}
+
+type AsyncSourcesDirContext = Async<TemplateDirContext<Sources>>;
+
+#[tearup_test(AsyncSourcesDirContext)]
+async fn it_starts_from_a_copy_of_the_template_in_async_test(dir: TempDir) {
+    assert!(dir.path().join("temp_dir.rs").exists());
+}
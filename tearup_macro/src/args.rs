@@ -0,0 +1,57 @@
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Expr, Ident, Token,
+};
+
+/// Arguments of the attribute: the context then some optional `key = value`
+/// e.g. `#[tearup_test(MyContext, some_option = 42)]`
+pub struct Args {
+    pub context: Ident,
+    options: Vec<Opt>,
+}
+
+struct Opt {
+    key: Ident,
+    value: Expr,
+}
+
+impl Args {
+    /// Remove the option from the remaining ones and return its value if any.
+    pub fn take(&mut self, key: &str) -> Option<Expr> {
+        let index = self.options.iter().position(|opt| opt.key == key)?;
+        Some(self.options.remove(index).value)
+    }
+
+    /// Panic if an option has not been consumed.
+    pub fn finish(self) {
+        if let Some(opt) = self.options.first() {
+            panic!("Unknown option '{}' for context {}.", opt.key, self.context)
+        }
+    }
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let context = input.parse()?;
+
+        let mut options = vec![];
+        if input.parse::<Option<Token![,]>>()?.is_some() {
+            options = Punctuated::<Opt, Token![,]>::parse_terminated(input)?
+                .into_iter()
+                .collect();
+        }
+
+        Ok(Self { context, options })
+    }
+}
+
+impl Parse for Opt {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+
+        Ok(Self { key, value })
+    }
+}
@@ -0,0 +1,86 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, ExprArray, ExprLit, ExprPath, ExprTuple, Ident, Lit};
+
+use crate::args::Args;
+
+/// Process wide state held from before the setup to after the teardown:
+/// - `capture_logs = true` buffers the `tracing` events, printed if the test fails
+/// - `serial = "group"`, with `cross_process = true` to also lock a file, added to the groups of the context
+/// - `env = [("KEY", "value"), ("REMOVED", None)]`
+/// - `cwd = "relative/to/the/crate"`
+/// - the registration of the context as live, `tearup_live`, to tear it down on SIGINT or SIGTERM
+///
+/// Expects the context trait in scope.
+pub fn from_args(args: &mut Args, name: &Ident) -> TokenStream {
+    let context = args.context.clone();
+    let capture_logs = args
+        .take("capture_logs")
+        .filter(|value| bool_value(value, "capture_logs"))
+        .map(|_| quote!(let _tearup_logs = tearup::LogCapture::start();));
+    let cross_process = args
+        .take("cross_process")
+        .map(|value| bool_value(&value, "cross_process"));
+    let group = match (args.take("serial"), cross_process) {
+        (Some(group), Some(true)) => {
+            quote!(Some(tearup::Serial::group(#group).cross_process()))
+        }
+        (Some(group), _) => quote!(Some(tearup::Serial::group(#group))),
+        (None, Some(_)) => panic!("'cross_process' needs a 'serial' group."),
+        (None, None) => quote!(None),
+    };
+    let serial = quote! {
+        let _tearup_serial = tearup::SerialGuard::acquire(#context::serial().into_iter().chain(#group));
+    };
+
+    let env = args.take("env").map(|env| {
+        let vars = env_vars(&env);
+        quote!(let _tearup_env = tearup::helper::EnvGuard::acquire([#(#vars),*]);)
+    });
+    let cwd = args
+        .take("cwd")
+        .map(|cwd| quote!(let _tearup_cwd = tearup::helper::CwdGuard::acquire(#cwd);));
+
+    let live = quote! {
+        let tearup_live = tearup::LiveContext::enter(
+            concat!(module_path!(), "::", stringify!(#name)),
+            std::any::type_name::<#context>(),
+            #context::fallback_teardown,
+        );
+    };
+
+    quote!(#capture_logs #serial #env #cwd #live)
+}
+
+fn env_vars(env: &Expr) -> Vec<TokenStream> {
+    let vars = match env {
+        Expr::Array(ExprArray { elems, .. }) => elems,
+        _ => panic!("Expected 'env = [(\"KEY\", \"value\"), ..]'."),
+    };
+
+    vars.iter()
+        .map(|var| match var {
+            Expr::Tuple(ExprTuple { elems, .. }) if elems.len() == 2 => {
+                let (key, value) = (&elems[0], &elems[1]);
+                let value = match value {
+                    Expr::Path(ExprPath { path, .. }) if path.is_ident("None") => {
+                        quote!(None::<String>)
+                    }
+                    value => quote!(Some((#value).to_string())),
+                };
+                quote!(((#key).to_string(), #value))
+            }
+            _ => panic!("Expected 'env = [(\"KEY\", \"value\"), ..]'."),
+        })
+        .collect()
+}
+
+fn bool_value(expr: &Expr, name: &str) -> bool {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Bool(value),
+            ..
+        }) => value.value,
+        _ => panic!("Expected '{} = true' or 'false'.", name),
+    }
+}
@@ -0,0 +1,26 @@
+use syn::{FnArg, PatType, Signature};
+
+/// Split the arguments between the fixtures taken from the `SharedContext`
+/// and the ones marked `#[passthrough]`, left in the signature for another test attribute to provide.
+pub fn split_inputs(sig: &Signature) -> (Vec<PatType>, Vec<PatType>) {
+    let mut fixtures = vec![];
+    let mut passthrough = vec![];
+
+    for arg in &sig.inputs {
+        let mut arg = match arg {
+            FnArg::Typed(arg) => arg.clone(),
+            _ => panic!("You should not pass this 'self' args"),
+        };
+
+        let attrs_count = arg.attrs.len();
+        arg.attrs.retain(|attr| !attr.path.is_ident("passthrough"));
+
+        if arg.attrs.len() < attrs_count {
+            passthrough.push(arg);
+        } else {
+            fixtures.push(arg);
+        }
+    }
+
+    (fixtures, passthrough)
+}
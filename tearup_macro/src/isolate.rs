@@ -0,0 +1,63 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Expr, ExprLit, Ident, Lit, Signature};
+
+use crate::{args::Args, inputs::split_inputs};
+
+/// `isolate = "process"` runs the test in a child process, see `tearup::isolate`.
+pub fn from_args(args: &mut Args, sig: &Signature) -> bool {
+    let isolate = match args.take("isolate") {
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Str(value),
+            ..
+        })) if value.value() == "process" => true,
+        Some(_) => panic!("Expected 'isolate = \"process\"'."),
+        None => return false,
+    };
+
+    if args.take("test_attr").is_some() || !split_inputs(sig).1.is_empty() {
+        panic!("'isolate' can't be used with 'test_attr' nor '#[passthrough]' arguments.")
+    }
+    isolate
+}
+
+/// A fn named like the test fn `inner`, running it in a child process.
+///
+/// The attributes, e.g. `#[should_panic]`, are seen by the harness of the child too,
+/// the parent relies on what the child prints instead.
+pub fn wrap(
+    context: &Ident,
+    sig: &Signature,
+    attrs: &[Attribute],
+    inner: TokenStream,
+) -> TokenStream {
+    let name = &sig.ident;
+    let (asyncness, run, context_trait) = match sig.asyncness {
+        Some(_) => (
+            quote!(async),
+            quote!(tearup::isolate::run_as_child_async(#name()).await),
+            quote!(tearup::AsyncContext),
+        ),
+        None => (
+            quote!(),
+            quote!(tearup::isolate::run_as_child(#name)),
+            quote!(tearup::Context),
+        ),
+    };
+
+    quote! {
+
+        #(#attrs)* #asyncness fn #name() {
+            #inner
+
+            if tearup::isolate::is_child(module_path!(), stringify!(#name)) {
+                #run
+            } else {
+                tearup::isolate::run_in_child(module_path!(), stringify!(#name), |outcome| {
+                    <#context as #context_trait>::fallback_teardown(outcome)
+                });
+            }
+        }
+
+    }
+}
@@ -1,8 +1,16 @@
 use proc_macro::TokenStream;
-use syn::{parse_quote, Ident, ItemFn};
+use syn::{parse_quote, Attribute, ItemFn, Stmt};
 
+mod args;
+use args::Args;
 #[cfg(feature = "async")]
 mod asyncc;
+mod guards;
+mod inputs;
+mod isolate;
+mod proptest;
+#[cfg(feature = "async")]
+mod runtime;
 mod sync;
 
 /// Same as `tearup_test` but does not add turn you function into a `#[test]`
@@ -11,14 +19,28 @@ pub fn tearup(attr: TokenStream, input: TokenStream) -> TokenStream {
     tearup_body(attr, input, false)
 }
 
+/// Executes the `setup` then the function and finally the `teardown`, as a `#[test]`.
+///
+/// - `test_attr = some::test` replaces the generated `#[test]`/`#[tokio::test]`,
+///   arguments marked `#[passthrough]` are then left for it to provide
+/// - `serial = "group"` runs the tests of the group one at a time, also across processes with `cross_process = true`
+/// - `env = [("KEY", "value"), ("REMOVED", None)]` sets environment variables for the whole test
+/// - `cwd = "some/dir"` moves to a directory of the crate for the whole test
+/// - `isolate = "process"` runs the setup, the test and the teardown in a child process
+/// - `capture_logs = true` prints the `tracing` events of the test only if it fails, needs the `tracing` feature
+/// - nothing is added if the function already has a test attribute: `#[test]`, `#[tokio::test]`...
+///   any path whose last segment is `test`, `test_case`, `test_matrix` or `rstest`
 #[proc_macro_attribute]
 pub fn tearup_test(attr: TokenStream, input: TokenStream) -> TokenStream {
     tearup_body(attr, input, true)
 }
 
-#[cfg(not(feature = "async"))]
-fn tearup_body(attr: TokenStream, input: TokenStream, test: bool) -> TokenStream {
-    let context = syn::parse_macro_input!(attr as Ident);
+/// Same as `tearup_test` but runs the body as a property with `proptest`.
+/// Arguments annotated with `#[strategy(..)]` are generated, the other ones are taken from the context.
+/// The setup runs once for all the cases, use `reset = my_reset_fn` to clean the context between two cases.
+#[proc_macro_attribute]
+pub fn tearup_proptest(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(attr as Args);
     let input = syn::parse_macro_input!(input as ItemFn);
 
     let ItemFn {
@@ -30,18 +52,33 @@ fn tearup_body(attr: TokenStream, input: TokenStream, test: bool) -> TokenStream
     let stmts = &block.stmts;
 
     if sig.asyncness.is_some() {
-        panic!("You need to turn on the 'async' feature on tearup to use it on async fn.")
+        panic!("tearup_proptest can't be used on async fn.")
     }
 
-    if test {
-        attrs.push(parse_quote!(#[test]));
+    attrs.push(parse_quote!(#[test]));
+    proptest::body(args, sig, attrs, stmts)
+}
+
+#[cfg(not(feature = "async"))]
+fn tearup_body(attr: TokenStream, input: TokenStream, test: bool) -> TokenStream {
+    let args = syn::parse_macro_input!(attr as Args);
+    let input = syn::parse_macro_input!(input as ItemFn);
+
+    let ItemFn {
+        attrs, sig, block, ..
+    } = input;
+    let stmts = &block.stmts;
+
+    if sig.asyncness.is_some() {
+        panic!("You need to turn on the 'async' feature on tearup to use it on async fn.")
     }
-    sync::body(context, sig, attrs, stmts)
+
+    sync_body(args, sig, attrs, stmts, test)
 }
 
 #[cfg(feature = "async")]
 fn tearup_body(attr: TokenStream, input: TokenStream, test: bool) -> TokenStream {
-    let context = syn::parse_macro_input!(attr as Ident);
+    let mut args = syn::parse_macro_input!(attr as Args);
     let input = syn::parse_macro_input!(input as ItemFn);
 
     let ItemFn {
@@ -52,15 +89,91 @@ fn tearup_body(attr: TokenStream, input: TokenStream, test: bool) -> TokenStream
     } = input;
     let stmts = &block.stmts;
 
-    if sig.asyncness.is_some() {
-        if test {
-            attrs.push(parse_quote!(#[tokio::test]));
+    if sig.asyncness.is_none() {
+        return sync_body(args, sig, attrs, stmts, test);
+    }
+
+    let context = args.context.clone();
+    let isolate = isolate::from_args(&mut args, &sig);
+    let test_attr = take_test_attr(&mut args, test);
+    let guards = guards::from_args(&mut args, &sig.ident);
+    let runtime = runtime::Runtime::from_args(&mut args);
+    args.finish();
+
+    let mut block_on = false;
+    if test {
+        if let Some(test_attr) = test_attr {
+            attrs.insert(0, parse_quote!(#[#test_attr]));
+        } else if !has_test_attr(&attrs) {
+            match runtime.test_attr() {
+                Some(test_attr) => attrs.push(parse_quote!(#test_attr)),
+                None => block_on = true,
+            }
+        }
+    }
+
+    let name = sig.ident.clone();
+    // Without test attribute for the runtime the attributes go on the blocking `#[test]`
+    let (fn_attrs, test_attrs) = match block_on {
+        true => (vec![], attrs),
+        false => (attrs, vec![]),
+    };
+    let async_fn = match isolate {
+        true => {
+            let inner = asyncc::body(context.clone(), guards, sig.clone(), vec![], stmts);
+            isolate::wrap(&context, &sig, &fn_attrs, inner.into()).into()
         }
-        asyncc::body(context, sig, attrs, stmts)
-    } else {
-        if test {
+        false => asyncc::body(context, guards, sig, fn_attrs, stmts),
+    };
+
+    match block_on {
+        true => asyncc::block_on_body(&name, test_attrs, async_fn, runtime.block_on()),
+        false => async_fn,
+    }
+}
+
+fn sync_body(
+    mut args: Args,
+    sig: syn::Signature,
+    mut attrs: Vec<Attribute>,
+    stmts: &Vec<Stmt>,
+    test: bool,
+) -> TokenStream {
+    let context = args.context.clone();
+    let isolate = isolate::from_args(&mut args, &sig);
+    let test_attr = take_test_attr(&mut args, test);
+    let guards = guards::from_args(&mut args, &sig.ident);
+    args.finish();
+
+    if test {
+        if let Some(test_attr) = test_attr {
+            attrs.insert(0, parse_quote!(#[#test_attr]));
+        } else if !has_test_attr(&attrs) {
             attrs.push(parse_quote!(#[test]));
         }
-        sync::body(context, sig, attrs, stmts)
     }
+
+    if isolate {
+        let inner = sync::body(context.clone(), guards, sig.clone(), vec![], stmts);
+        return isolate::wrap(&context, &sig, &attrs, inner.into()).into();
+    }
+    sync::body(context, guards, sig, attrs, stmts)
+}
+
+/// The last segment of the attributes generating the tests themselves, `#[test]`, `#[tokio::test]` or `#[test_log::test]` end with `test`.
+const TEST_ATTRS: [&str; 4] = ["test", "test_case", "test_matrix", "rstest"];
+
+/// Whether another attribute already turns the fn into a test.
+fn has_test_attr(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        matches!(attr.path.segments.last(), Some(segment) if TEST_ATTRS.iter().any(|test| segment.ident == test))
+    })
+}
+
+fn take_test_attr(args: &mut Args, test: bool) -> Option<syn::Expr> {
+    let test_attr = args.take("test_attr");
+    if test_attr.is_some() && !test {
+        panic!("'test_attr' can only be used with tearup_test, tearup adds no test attribute.")
+    }
+    test_attr
 }
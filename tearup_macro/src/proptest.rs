@@ -0,0 +1,187 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Expr, FnArg, Pat, PatType, Stmt};
+
+use crate::{args::Args, guards};
+
+pub fn body(
+    mut args: Args,
+    sig: syn::Signature,
+    attrs: Vec<Attribute>,
+    stmts: &Vec<Stmt>,
+) -> TokenStream {
+    let context = args.context.clone();
+    let cases = args.take("cases");
+    let reset = args.take("reset");
+    let guards = guards::from_args(&mut args, &sig.ident);
+    args.finish();
+
+    let name = sig.ident.clone();
+    let (fixtures, generated) = split_args(&sig);
+
+    if generated.is_empty() {
+        panic!("tearup_proptest needs at least one '#[strategy(..)]' argument, use tearup_test otherwise.")
+    }
+
+    let fixture_idents = (0..fixtures.len())
+        .map(|i| format_ident!("__tearup_fixture_{}", i))
+        .collect::<Vec<_>>();
+    let fixture_pats = fixtures.iter().map(|arg| &arg.pat).collect::<Vec<_>>();
+    let fixture_tys = fixtures.iter().map(|arg| &arg.ty).collect::<Vec<_>>();
+    let fixture_names = fixtures.iter().map(|arg| match &*arg.pat {
+        Pat::Ident(pat) => pat.ident.to_string(),
+        pat => quote!(#pat).to_string(),
+    });
+
+    let strategies = generated.iter().map(|(_, strategy)| strategy);
+    let generated_pats = generated.iter().map(|(arg, _)| &arg.pat);
+    let generated_tys = generated.iter().map(|(arg, _)| &arg.ty);
+
+    let config = match cases {
+        Some(cases) => quote!(Config { cases: #cases, source_file: Some(file!()), ..Config::default() }),
+        None => quote!(Config { source_file: Some(file!()), ..Config::default() }),
+    };
+
+    let reset_state = reset.as_ref().map(|_| {
+        quote! {
+            let first_case = std::cell::Cell::new(true);
+        }
+    });
+    let reset_between_cases = reset.map(|reset| {
+        quote! {
+            if !first_case.replace(false) {
+                let (held_context, shared_context) = &mut *state.borrow_mut();
+                // Taken back for the reset, unless the signal thread is tearing it down
+                if let Some(mut context) = held_context.take().and_then(|held| held.claim(&tearup_live)) {
+                    #reset(&mut context, shared_context);
+                    *held_context = Some((&&tearup::LiveInstance::new(context)).hold(&tearup_live, shared_context));
+                }
+            }
+        }
+    });
+
+    let result = quote! {
+
+        #(#attrs)* fn #name() {
+            use tearup::{SharedContext, Context, PropFailure, HoldInstance as _, KeepInstance as _};
+            use tearup::proptest::test_runner::{Config, TestRunner};
+
+            #guards
+            let leak_watch = tearup::LeakWatch::start(std::any::type_name::<#context>(), #context::LEAK_CHECK);
+
+            let test_name = concat!(module_path!(), "::", stringify!(#name));
+            let mut shared_context = SharedContext::default();
+            shared_context.register(tearup::TestName(test_name));
+            let slot_scope = tearup::SlotScope::default();
+            shared_context.register(slot_scope.held());
+            let context = #context::launch_setup(&mut shared_context);
+            // Handed over during the test, for the signal thread to tear it down on SIGINT or SIGTERM
+            let held_context = (&&tearup::LiveInstance::new(context)).hold(&tearup_live, &shared_context);
+
+            let mut runner = TestRunner::new(#config);
+
+            let observation = tearup::Observation::start(
+                std::any::type_name::<#context>(),
+                Some(test_name),
+                #context::observers(),
+                tearup::TearupEventKind::TestStarted,
+            );
+
+            // The fixtures of the last failing case, the minimal one once shrunk
+            let failing_fixtures = std::cell::RefCell::new(String::new());
+            let state = std::cell::RefCell::new((Some(held_context), &mut shared_context));
+            let text_execution = observation.in_scope(|| std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                #reset_state
+                runner.run(
+                    &(#(#strategies,)*),
+                    |(#(#generated_pats,)*): (#(#generated_tys,)*)| {
+                        #reset_between_cases
+                        #(let #fixture_idents: #fixture_tys = state.borrow_mut().1.get().unwrap();)*
+                        let fixtures = {
+                            use tearup::{DebugFixture as _, OpaqueFixture as _};
+                            let fixtures: Vec<String> = vec![#(format!("{} = {}", #fixture_names, (&&tearup::Fixture(&#fixture_idents)).describe())),*];
+                            fixtures.join(", ")
+                        };
+                        #(let #fixture_pats: #fixture_tys = #fixture_idents;)*
+
+                        let case = std::panic::catch_unwind(std::panic::AssertUnwindSafe(
+                            || -> Result<(), tearup::proptest::test_runner::TestCaseError> {
+                                #(#stmts)*
+
+                                Ok(())
+                            },
+                        ));
+                        if !matches!(case, Ok(Ok(()))) {
+                            *failing_fixtures.borrow_mut() = fixtures;
+                        }
+                        case.unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                    },
+                )
+            })));
+
+            let held_context = state.into_inner().0;
+
+            let failure = match &text_execution {
+                Ok(Err(err)) => Some(PropFailure {
+                    context: stringify!(#context),
+                    fixtures: failing_fixtures.into_inner(),
+                    reason: err.to_string(),
+                }),
+                _ => None,
+            };
+            let outcome = match &failure {
+                Some(failure) => {
+                    shared_context.register(failure.clone());
+                    tearup::TestOutcome::Failed(failure.to_string())
+                }
+                None => tearup::TestOutcome::of(&text_execution),
+            };
+            observation.finish(|duration| tearup::TearupEventKind::TestFinished { duration, outcome: outcome.clone() });
+            shared_context.register(outcome);
+
+            if let Some(context) = held_context.and_then(|held| held.claim(&tearup_live)) {
+                context.launch_teardown(&mut shared_context);
+            }
+            leak_watch.finish(text_execution.is_err() || failure.is_some());
+
+            match text_execution {
+                Err(err) => std::panic::resume_unwind(err),
+                Ok(_) => if let Some(failure) = failure {
+                    panic!("{}", failure)
+                },
+            }
+        }
+
+    };
+    result.into()
+}
+
+fn split_args(sig: &syn::Signature) -> (Vec<PatType>, Vec<(PatType, Expr)>) {
+    let mut fixtures = vec![];
+    let mut generated = vec![];
+
+    for arg in &sig.inputs {
+        let mut arg = match arg {
+            FnArg::Typed(arg) => arg.clone(),
+            _ => panic!("You should not pass this 'self' args"),
+        };
+
+        let strategy = arg
+            .attrs
+            .iter()
+            .position(|attr| attr.path.is_ident("strategy"))
+            .map(|index| arg.attrs.remove(index));
+
+        match strategy {
+            Some(strategy) => {
+                let strategy = strategy
+                    .parse_args::<Expr>()
+                    .unwrap_or_else(|_| panic!("Expected '#[strategy(<expression>)]'"));
+                generated.push((arg, strategy));
+            }
+            None => fixtures.push(arg),
+        }
+    }
+
+    (fixtures, generated)
+}
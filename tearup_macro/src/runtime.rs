@@ -0,0 +1,87 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, ExprLit, Lit};
+
+use crate::args::Args;
+
+/// Options forwarded to `#[tokio::test(..)]`.
+const TOKIO_OPTIONS: [&str; 3] = ["flavor", "worker_threads", "start_paused"];
+
+/// The runtime driving an async test, chosen with `runtime = "tokio" | "async_std" | "smol"`.
+/// Defaults to the first runtime feature enabled.
+pub enum Runtime {
+    Tokio(TokenStream),
+    AsyncStd,
+    Smol,
+}
+
+impl Runtime {
+    pub fn from_args(args: &mut Args) -> Self {
+        let tokio_options = TOKIO_OPTIONS
+            .iter()
+            .filter_map(|key| {
+                let value = args.take(key)?;
+                let key = syn::Ident::new(key, proc_macro2::Span::call_site());
+                Some(quote!(#key = #value))
+            })
+            .collect::<Vec<_>>();
+
+        let runtime = match args.take("runtime") {
+            Some(runtime) => string_value(&runtime),
+            None => default_runtime().to_owned(),
+        };
+
+        match runtime.as_str() {
+            "tokio" => Runtime::Tokio(quote!(#(#tokio_options),*)),
+            _ if !tokio_options.is_empty() => {
+                panic!("Options {:?} are only available with the tokio runtime.", TOKIO_OPTIONS)
+            }
+            "async_std" => Runtime::AsyncStd,
+            "smol" => Runtime::Smol,
+            _ => panic!(
+                "Unknown runtime '{}', expected 'tokio', 'async_std' or 'smol'.",
+                runtime
+            ),
+        }
+    }
+
+    /// The attribute turning the async fn into a test, `None` when a `#[test]` blocks on it instead.
+    pub fn test_attr(&self) -> Option<TokenStream> {
+        match self {
+            Runtime::Tokio(options) if options.is_empty() => Some(quote!(#[tokio::test])),
+            Runtime::Tokio(options) => Some(quote!(#[tokio::test(#options)])),
+            Runtime::AsyncStd | Runtime::Smol => None,
+        }
+    }
+
+    /// Blocks on the async fn, through tearup so the test crate doesn't need to depend on the runtime.
+    pub fn block_on(&self) -> TokenStream {
+        match self {
+            Runtime::Tokio(_) => unreachable!("tokio tests have an attribute"),
+            Runtime::AsyncStd => quote!(tearup::__async_std::task::block_on),
+            Runtime::Smol => quote!(tearup::__smol::block_on),
+        }
+    }
+}
+
+fn string_value(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(value),
+            ..
+        }) => value.value(),
+        _ => panic!("Expected a string literal for 'runtime'."),
+    }
+}
+
+fn default_runtime() -> &'static str {
+    if cfg!(feature = "tokio") {
+        "tokio"
+    } else if cfg!(feature = "async-std") {
+        "async_std"
+    } else if cfg!(feature = "smol") {
+        "smol"
+    } else {
+        panic!("You need to turn on a runtime feature ('tokio', 'async-std' or 'smol') on tearup to use it on async fn.")
+    }
+}
@@ -1,31 +1,60 @@
 use proc_macro::TokenStream;
 use quote::{quote, ToTokens};
-use syn::{punctuated::Punctuated, token::Semi, Attribute, FnArg, Ident, Stmt};
+use syn::{punctuated::Punctuated, token::Semi, Attribute, Ident, PatType, Stmt};
+
+use crate::inputs::split_inputs;
 
 pub fn body(
     context: Ident,
+    guards: proc_macro2::TokenStream,
     sig: syn::Signature,
     attrs: Vec<Attribute>,
     stmts: &Vec<Stmt>,
 ) -> TokenStream {
     let name = sig.ident.clone();
-    let let_args = define_args(&sig);
+    let (fixtures, passthrough) = split_inputs(&sig);
+    let let_args = define_args(&fixtures);
 
     let result = quote! {
 
-        #(#attrs)* fn #name() {
+        #(#attrs)* fn #name(#(#passthrough),*) {
             use tearup::{SharedContext, Context};
 
+            #guards
+            let leak_watch = tearup::LeakWatch::start(std::any::type_name::<#context>(), #context::LEAK_CHECK);
+
+            let test_name = concat!(module_path!(), "::", stringify!(#name));
             let mut shared_context = SharedContext::default();
-            let mut context = #context::launch_setup(&mut shared_context);
+            shared_context.register(tearup::TestName(test_name));
+            let slot_scope = tearup::SlotScope::default();
+            shared_context.register(slot_scope.held());
+            let context = #context::launch_setup(&mut shared_context);
 
             #let_args
 
-            let text_execution = context.launch_test(move || {
+            // Handed over during the test, for the signal thread to tear it down on SIGINT or SIGTERM
+            let held_context = {
+                use tearup::{HoldInstance as _, KeepInstance as _};
+                (&&tearup::LiveInstance::new(context)).hold(&tearup_live, &shared_context)
+            };
+
+            let observation = tearup::Observation::start(
+                std::any::type_name::<#context>(),
+                Some(test_name),
+                #context::observers(),
+                tearup::TearupEventKind::TestStarted,
+            );
+            let text_execution = observation.in_scope(|| #context::launch_test(move || {
                 #(#stmts)*
-            });
+            }));
+            let outcome = tearup::TestOutcome::of(&text_execution);
+            observation.finish(|duration| tearup::TearupEventKind::TestFinished { duration, outcome: outcome.clone() });
+            shared_context.register(outcome);
 
-            context.launch_teardown(&mut shared_context);
+            if let Some(context) = held_context.claim(&tearup_live) {
+                context.launch_teardown(&mut shared_context);
+            }
+            leak_watch.finish(text_execution.is_err());
 
             if let Err(err) = text_execution {
                 std::panic::resume_unwind(err)
@@ -36,17 +65,14 @@ pub fn body(
     result.into()
 }
 
-fn define_args(sig: &syn::Signature) -> Punctuated<proc_macro2::TokenStream, Semi> {
-    sig.inputs
+fn define_args(fixtures: &[PatType]) -> Punctuated<proc_macro2::TokenStream, Semi> {
+    fixtures
         .iter()
-        .map(|arg| match arg {
-            FnArg::Typed(arg) => define_arg(arg),
-            _ => panic!("You should not pass this 'self' args"),
-        })
+        .map(define_arg)
         .collect::<Punctuated<proc_macro2::TokenStream, Semi>>()
 }
 
-fn define_arg(arg: &syn::PatType) -> proc_macro2::TokenStream {
+fn define_arg(arg: &PatType) -> proc_macro2::TokenStream {
     let name = &arg.pat;
     let ty = &arg.ty;
     quote! {